@@ -1,6 +1,7 @@
 mod loading_indicator;
 mod shimmer_effect;
 mod spoiler;
+mod spoiler_label;
 
 use adw::prelude::*;
 use adw::subclass::prelude::*;
@@ -27,6 +28,7 @@ mod imp {
             loading_indicator::LoadingIndicatorPage::static_type();
             shimmer_effect::ShimmerEffectPage::static_type();
             spoiler::SpoilerPage::static_type();
+            spoiler_label::SpoilerLabelPage::static_type();
 
             klass.bind_template();
         }
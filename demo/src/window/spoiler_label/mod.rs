@@ -0,0 +1,60 @@
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate)]
+    #[template(file = "src/window/spoiler_label/spoiler_label.blp")]
+    pub struct SpoilerLabelPage {
+        #[template_child]
+        pub(super) spoiler_label: TemplateChild<ori::SpoilerLabel>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for SpoilerLabelPage {
+        const NAME: &'static str = "OriDemoSpoilerLabelPage";
+        type Type = super::SpoilerLabelPage;
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_callbacks();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for SpoilerLabelPage {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let text = self.spoiler_label.text();
+
+            let ranges: Vec<_> = ["kills Dumbledore", "was dead", "The butler"]
+                .into_iter()
+                .filter_map(|spoiler| text.find(spoiler).map(|start| start..start + spoiler.len()))
+                .collect();
+
+            self.spoiler_label.set_spoilers(&ranges);
+        }
+    }
+
+    impl WidgetImpl for SpoilerLabelPage {}
+    impl BinImpl for SpoilerLabelPage {}
+
+    #[gtk::template_callbacks]
+    impl SpoilerLabelPage {
+        #[template_callback]
+        fn hide_spoilers(&self) {
+            self.spoiler_label.hide_spoilers();
+        }
+    }
+}
+
+glib::wrapper! {
+    pub struct SpoilerLabelPage(ObjectSubclass<imp::SpoilerLabelPage>)
+        @extends adw::Bin, gtk::Widget;
+}
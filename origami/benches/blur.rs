@@ -0,0 +1,110 @@
+//! Compares blur backends of the SpoilerOverlay on a 4K image
+//!
+//! Needs a display and a GPU renderer, run with `cargo bench --bench blur`
+
+use adw::prelude::*;
+use gtk::{gdk, glib, graphene};
+use std::time::{Duration, Instant};
+
+const WIDTH: i32 = 3840;
+const HEIGHT: i32 = 2160;
+const ITERATIONS: u32 = 20;
+
+fn main() {
+    if gtk::init().is_err() {
+        eprintln!("No display available, skipping the blur benchmark");
+        return;
+    }
+
+    adw::init().unwrap();
+
+    let overlay = glib::Object::new::<origami::SpoilerOverlay>();
+    overlay.set_child(Some(&gtk::Picture::for_paintable(&image())));
+    overlay.set_hidden(true);
+    overlay.set_reveal_progress(0.0);
+
+    let window = gtk::Window::builder()
+        .child(&overlay)
+        .default_width(WIDTH)
+        .default_height(HEIGHT)
+        .build();
+    window.present();
+
+    let context = glib::MainContext::default();
+    while !overlay.is_mapped() || overlay.width() == 0 {
+        context.iteration(true);
+    }
+
+    for backend in [
+        origami::BlurBackend::GlShader,
+        origami::BlurBackend::Gaussian,
+        origami::BlurBackend::BlurNode,
+    ] {
+        overlay.set_blur_backend(backend);
+
+        // Compiles the shader
+        render(&overlay);
+
+        let mut total = Duration::ZERO;
+
+        for _ in 0..ITERATIONS {
+            overlay.refresh_blur();
+            origami::BlurCache::clear();
+
+            let start = Instant::now();
+            render(&overlay);
+            total += start.elapsed();
+        }
+
+        println!(
+            "{backend:?}: {:.2} ms per frame at {}x{}",
+            total.as_secs_f64() * 1000.0 / ITERATIONS as f64,
+            overlay.width(),
+            overlay.height(),
+        );
+    }
+}
+
+/// Renders the overlay and waits for the result
+fn render(overlay: &origami::SpoilerOverlay) {
+    let width = overlay.width();
+    let height = overlay.height();
+
+    let paintable = gtk::WidgetPaintable::new(Some(overlay));
+    let snapshot = gtk::Snapshot::new();
+    paintable.snapshot(&snapshot, width as f64, height as f64);
+
+    let Some(node) = snapshot.to_node() else {
+        return;
+    };
+
+    let renderer = overlay.native().unwrap().renderer();
+    let viewport = graphene::Rect::new(0.0, 0.0, width as f32, height as f32);
+    let texture = renderer.render_texture(node, Some(&viewport));
+
+    // Downloading waits until the GPU finishes
+    let stride = 4 * texture.width() as usize;
+    let mut data = vec![0; stride * texture.height() as usize];
+    texture.download(&mut data, stride);
+}
+
+/// Noisy image, so the blur can't be skipped
+fn image() -> gdk::Texture {
+    let mut data = Vec::with_capacity((WIDTH * HEIGHT * 4) as usize);
+
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            let noise = ((x * 7919) ^ (y * 104729)) as u8;
+            data.extend_from_slice(&[noise, (x / 16) as u8, (y / 16) as u8, 255]);
+        }
+    }
+
+    gdk::MemoryTexture::new(
+        WIDTH,
+        HEIGHT,
+        gdk::MemoryFormat::R8g8b8a8,
+        &glib::Bytes::from_owned(data),
+        (WIDTH * 4) as usize,
+    )
+    .upcast()
+}
@@ -0,0 +1,454 @@
+//! Shared frame scheduling of the animated widgets
+//!
+//! Every widget owns a [Ticker], tickers of widgets drawn by the same frame clock
+//! are grouped together and ticked from a single frame clock update.
+
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::{gdk, glib};
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+
+use crate::time_source::{FrameClockTime, TimeSource};
+
+mod imp {
+    use super::*;
+
+    #[derive(glib::Properties)]
+    #[properties(wrapper_type = super::AnimationDriver)]
+    pub struct AnimationDriver {
+        #[property(get, set, minimum = 0.0, default_value = 1.0)]
+        pub(super) speed: Cell<f64>,
+
+        #[property(get, set)]
+        pub(super) max_fps: Cell<u32>,
+
+        #[property(get, set = Self::set_pause_on_backdrop)]
+        pub(super) pause_on_backdrop: Cell<bool>,
+
+        pub(super) clocks: RefCell<Vec<Weak<ClockEntry>>>,
+        pub(super) time_source: RefCell<Rc<dyn TimeSource>>,
+    }
+
+    impl Default for AnimationDriver {
+        fn default() -> Self {
+            Self {
+                speed: Cell::new(1.0),
+                max_fps: Default::default(),
+                pause_on_backdrop: Default::default(),
+                clocks: Default::default(),
+                time_source: RefCell::new(Rc::new(FrameClockTime)),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for AnimationDriver {
+        const NAME: &'static str = "OriAnimationDriver";
+        type Type = super::AnimationDriver;
+    }
+
+    impl ObjectImpl for AnimationDriver {
+        fn properties() -> &'static [glib::ParamSpec] {
+            Self::derived_properties()
+        }
+
+        fn set_property(&self, id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            self.derived_set_property(id, value, pspec)
+        }
+
+        fn property(&self, id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            self.derived_property(id, pspec)
+        }
+    }
+
+    impl AnimationDriver {
+        fn set_pause_on_backdrop(&self, pause: bool) {
+            self.pause_on_backdrop.set(pause);
+
+            for entry in self.entries() {
+                entry.update();
+            }
+        }
+
+        pub(super) fn entries(&self) -> Vec<Rc<ClockEntry>> {
+            let mut clocks = self.clocks.borrow_mut();
+            clocks.retain(|entry| entry.strong_count() > 0);
+            clocks.iter().filter_map(Weak::upgrade).collect()
+        }
+
+        /// Finds the entry of the frame clock or creates a new one
+        pub(super) fn entry(
+            &self,
+            clock: &gdk::FrameClock,
+            surface: &gdk::Surface,
+        ) -> Rc<ClockEntry> {
+            if let Some(entry) = self
+                .entries()
+                .into_iter()
+                .find(|entry| &entry.clock == clock)
+            {
+                return entry;
+            }
+
+            let entry = ClockEntry::new(clock, surface);
+            self.clocks.borrow_mut().push(Rc::downgrade(&entry));
+            entry
+        }
+
+        /// Ticks all running animations without waiting for the frame clocks
+        pub(crate) fn dispatch(&self) {
+            for entry in self.entries() {
+                if entry.updating.get() {
+                    entry.dispatch();
+                }
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    #[doc(alias = "OriAnimationDriver")]
+    /// Drives animations of all origami widgets
+    ///
+    /// Widgets drawn in the same window share a single frame clock update,
+    /// and stop ticking while they aren't visible on the screen.
+    /// Animations of libadwaita used for reveals aren't affected by the driver.
+    ///
+    /// # Properties
+    ///
+    /// * Speed: [f64].
+    /// Multiplier of the animation speed, `0` freezes the animations. `1` by default.
+    ///
+    /// * Max FPS: [u32].
+    /// Highest frame rate of the animations, `0` means the frame rate of the monitor.
+    ///
+    /// * Pause on backdrop: [bool].
+    /// Whether animations are paused while their window isn't focused.
+    ///
+    /// # Time source
+    ///
+    /// Animations read the time through a [TimeSource], [FrameClockTime] by default.
+    /// Set [ManualClock](crate::ManualClock) with [AnimationDriver::set_time_source]
+    /// to step animations frame by frame in tests,
+    /// reveals and timers which don't follow the driver are listed in [TimeSource].
+    ///
+    /// # Example
+    /// ```no_run
+    /// let driver = origami::AnimationDriver::default();
+    /// driver.set_max_fps(30);
+    /// driver.set_pause_on_backdrop(true);
+    /// ```
+    pub struct AnimationDriver(ObjectSubclass<imp::AnimationDriver>);
+}
+
+impl Default for AnimationDriver {
+    /// Driver shared by all widgets of the thread
+    fn default() -> Self {
+        thread_local! {
+            static DRIVER: AnimationDriver = glib::Object::new();
+        }
+
+        DRIVER.with(Clone::clone)
+    }
+}
+
+impl AnimationDriver {
+    /// Replaces the source of the animation time
+    ///
+    /// Animations continue from the same frame with the new source
+    pub fn set_time_source(&self, source: impl TimeSource) {
+        for entry in self.imp().entries() {
+            entry.last_frame.set(Some(source.frame_time(&entry.clock)));
+        }
+
+        self.imp().time_source.replace(Rc::new(source));
+    }
+}
+
+/// Tickers of the widgets drawn by the same frame clock
+struct ClockEntry {
+    clock: gdk::FrameClock,
+    toplevel: Option<gdk::Surface>,
+    update_handler: RefCell<Option<glib::SignalHandlerId>>,
+    state_handler: RefCell<Option<glib::SignalHandlerId>>,
+    tickers: RefCell<Vec<Weak<TickerState>>>,
+    updating: Cell<bool>,
+    /// Time of the last frame delivered to the tickers
+    last_frame: Cell<Option<i64>>,
+    /// Time shared by the tickers of the entry
+    time: Cell<i64>,
+}
+
+impl ClockEntry {
+    fn new(clock: &gdk::FrameClock, surface: &gdk::Surface) -> Rc<Self> {
+        let entry = Rc::new(Self {
+            clock: clock.clone(),
+            toplevel: toplevel(surface),
+            update_handler: Default::default(),
+            state_handler: Default::default(),
+            tickers: Default::default(),
+            updating: Default::default(),
+            last_frame: Default::default(),
+            time: Default::default(),
+        });
+
+        let weak = Rc::downgrade(&entry);
+        let handler = clock.connect_update(move |_| {
+            if let Some(entry) = weak.upgrade() {
+                entry.dispatch();
+            }
+        });
+        entry.update_handler.replace(Some(handler));
+
+        if let Some(toplevel) = &entry.toplevel {
+            let weak = Rc::downgrade(&entry);
+            let handler = toplevel.connect_notify_local(Some("state"), move |_, _| {
+                if let Some(entry) = weak.upgrade() {
+                    entry.update();
+                }
+            });
+            entry.state_handler.replace(Some(handler));
+        }
+
+        entry
+    }
+
+    fn add(&self, ticker: &Rc<TickerState>) {
+        self.tickers.borrow_mut().push(Rc::downgrade(ticker));
+        self.update();
+    }
+
+    fn remove(&self, ticker: &Rc<TickerState>) {
+        self.tickers.borrow_mut().retain(|other| {
+            other
+                .upgrade()
+                .is_some_and(|other| !Rc::ptr_eq(&other, ticker))
+        });
+        self.update();
+    }
+
+    /// Starts or stops frame clock updates
+    fn update(&self) {
+        let updating = !self.tickers.borrow().is_empty() && !self.is_paused();
+
+        if self.updating.replace(updating) == updating {
+            return;
+        }
+
+        if updating {
+            // Time doesn't move while the entry is paused
+            self.last_frame.set(None);
+            self.clock.begin_updating();
+        } else {
+            self.clock.end_updating();
+        }
+    }
+
+    fn is_paused(&self) -> bool {
+        let Some(toplevel) = self
+            .toplevel
+            .as_ref()
+            .and_then(|s| s.downcast_ref::<gdk::Toplevel>())
+        else {
+            return false;
+        };
+
+        let state = toplevel.state();
+        let backdrop = !state.contains(gdk::ToplevelState::FOCUSED);
+
+        state.contains(gdk::ToplevelState::MINIMIZED)
+            || (backdrop && AnimationDriver::default().pause_on_backdrop())
+    }
+
+    fn dispatch(&self) {
+        let driver = AnimationDriver::default();
+        let frame_time = driver.imp().time_source.borrow().frame_time(&self.clock);
+
+        // Time of the new source can be behind
+        let elapsed = self.last_frame.get().map(|last| (frame_time - last).max(0));
+        let max_fps = driver.max_fps() as i64;
+
+        // Frames come at the monitor rate, so the interval is a bit shorter
+        // to not skip frames which arrived slightly earlier
+        if let (Some(elapsed), true) = (elapsed, max_fps > 0) {
+            if elapsed < 900_000 / max_fps {
+                return;
+            }
+        }
+
+        let delta = (elapsed.unwrap_or_default() as f64 * driver.speed()) as i64;
+
+        self.last_frame.set(Some(frame_time));
+        self.time.set(self.time.get() + delta);
+
+        let tickers: Vec<_> = self
+            .tickers
+            .borrow()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect();
+
+        for ticker in tickers {
+            TickerState::tick(&ticker, delta, self.time.get());
+        }
+    }
+}
+
+impl Drop for ClockEntry {
+    fn drop(&mut self) {
+        if let Some(handler) = self.update_handler.take() {
+            self.clock.disconnect(handler);
+        }
+
+        if let (Some(toplevel), Some(handler)) = (&self.toplevel, self.state_handler.take()) {
+            toplevel.disconnect(handler);
+        }
+
+        if self.updating.get() {
+            self.clock.end_updating();
+        }
+    }
+}
+
+type Callback = Rc<dyn Fn(&gtk::Widget) -> bool>;
+
+/// Animation clock of a widget
+///
+/// The callback is called every frame while the widget is mapped.
+/// Time of the paused ticker doesn't move,
+/// so animations continue from the same frame when the widget appears again
+#[derive(Default)]
+pub(crate) struct Ticker {
+    state: Rc<TickerState>,
+}
+
+#[derive(Default)]
+struct TickerState {
+    widget: glib::WeakRef<gtk::Widget>,
+    callback: RefCell<Option<Callback>>,
+    entry: RefCell<Option<Rc<ClockEntry>>>,
+    /// Time the ticker was running, scaled by the speed of the driver
+    time: Cell<i64>,
+    /// Time of the frame clock entry, shared with other widgets
+    clock_time: Cell<i64>,
+}
+
+impl Ticker {
+    /// Calls the `callback` every frame until it returns `false` or the ticker is stopped
+    ///
+    /// Replaces the previous callback
+    pub(crate) fn start<W: IsA<gtk::Widget>>(
+        &self,
+        widget: &W,
+        callback: impl Fn(&W) -> bool + 'static,
+    ) {
+        let callback: Callback = Rc::new(move |widget| callback(widget.downcast_ref().unwrap()));
+
+        self.state.widget.set(Some(widget.upcast_ref()));
+        self.state.callback.replace(Some(callback));
+        self.update(widget);
+    }
+
+    /// Stops the ticker and forgets its widget
+    ///
+    /// Works when the widget is already destroyed
+    pub(crate) fn reset(&self) {
+        self.state.callback.take();
+        self.state.widget.set(None);
+        TickerState::unregister(&self.state);
+    }
+
+    pub(crate) fn is_running(&self) -> bool {
+        self.state.callback.borrow().is_some()
+    }
+
+    /// Registers or unregisters the ticker depending on the widget visibility
+    ///
+    /// Should be called when the widget is mapped and unmapped
+    pub(crate) fn update(&self, widget: &impl IsA<gtk::Widget>) {
+        let widget = widget.upcast_ref();
+        let state = &self.state;
+
+        let clock = (self.is_running() && widget.is_mapped())
+            .then(|| widget.frame_clock())
+            .flatten();
+
+        let current_clock = state
+            .entry
+            .borrow()
+            .as_ref()
+            .map(|entry| entry.clock.clone());
+
+        if clock == current_clock {
+            return;
+        }
+
+        TickerState::unregister(state);
+
+        let surface = widget.native().and_then(|native| native.surface());
+
+        if let (Some(clock), Some(surface)) = (clock, surface) {
+            let entry = AnimationDriver::default().imp().entry(&clock, &surface);
+            entry.add(state);
+            state.entry.replace(Some(entry));
+        }
+    }
+
+    /// Time in microseconds the ticker was running
+    pub(crate) fn time(&self) -> i64 {
+        self.state.time.get()
+    }
+
+    /// Time in microseconds shared by all widgets of the window
+    ///
+    /// Unlike [Ticker::time] it doesn't stop while the widget is unmapped
+    pub(crate) fn clock_time(&self) -> i64 {
+        self.state.clock_time.get()
+    }
+}
+
+impl Drop for Ticker {
+    fn drop(&mut self) {
+        TickerState::unregister(&self.state);
+    }
+}
+
+impl TickerState {
+    fn tick(this: &Rc<Self>, delta: i64, clock_time: i64) {
+        this.time.set(this.time.get() + delta);
+        this.clock_time.set(clock_time);
+
+        let Some(widget) = this.widget.upgrade() else {
+            Self::unregister(this);
+            return;
+        };
+
+        let callback = this.callback.borrow().clone();
+        let keep_going = callback.is_some_and(|callback| callback(&widget));
+
+        if !keep_going {
+            this.callback.take();
+            Self::unregister(this);
+        }
+    }
+
+    fn unregister(this: &Rc<Self>) {
+        let entry = this.entry.take();
+
+        if let Some(entry) = entry {
+            entry.remove(this);
+        }
+    }
+}
+
+/// Surface which has the window state
+fn toplevel(surface: &gdk::Surface) -> Option<gdk::Surface> {
+    let mut surface = surface.clone();
+
+    while let Some(popup) = surface.downcast_ref::<gdk::Popup>() {
+        surface = popup.parent()?;
+    }
+
+    surface.is::<gdk::Toplevel>().then_some(surface)
+}
@@ -0,0 +1,350 @@
+//! Blurred textures shared between spoilers
+//!
+//! The same image is often hidden in several places at once,
+//! so it's blurred once and the texture is shared by all spoilers showing it.
+
+use gtk::prelude::*;
+use gtk::{gdk, glib};
+use std::cell::RefCell;
+
+/// Default memory budget of the cache in bytes
+const DEFAULT_BUDGET: usize = 64 * 1024 * 1024;
+
+/// Identity of the blurred texture
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct BlurKey {
+    /// Address of the blurred paintable
+    pub(crate) content: usize,
+    /// Logical size and the scale factor
+    pub(crate) size: (u32, u32, i32),
+    /// Hash of the blur settings
+    pub(crate) settings: u64,
+}
+
+struct Entry {
+    key: BlurKey,
+    /// Checked on lookup, because the address can be reused by another paintable
+    content: glib::WeakRef<gdk::Paintable>,
+    texture: gdk::Texture,
+    last_used: u64,
+}
+
+impl Entry {
+    fn memory(&self) -> usize {
+        texture_memory(&self.texture)
+    }
+}
+
+struct Cache {
+    entries: Vec<Entry>,
+    /// Counter used to find the least recently used entry
+    clock: u64,
+    budget: usize,
+    stats: BlurCacheStats,
+}
+
+impl Default for Cache {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            clock: 0,
+            budget: DEFAULT_BUDGET,
+            stats: Default::default(),
+        }
+    }
+}
+
+impl Cache {
+    fn memory(&self) -> usize {
+        self.entries.iter().map(Entry::memory).sum()
+    }
+
+    /// Removes entries of the destroyed paintables
+    fn prune(&mut self) {
+        self.entries
+            .retain(|entry| entry.content.upgrade().is_some());
+    }
+
+    /// Evicts least recently used entries until `extra` bytes fit into the budget
+    fn evict(&mut self, extra: usize) {
+        let mut memory = self.memory();
+
+        while memory + extra > self.budget {
+            let Some(index) = self
+                .entries
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(index, _)| index)
+            else {
+                break;
+            };
+
+            memory -= self.entries.remove(index).memory();
+            self.stats.evictions += 1;
+        }
+    }
+}
+
+thread_local! {
+    static CACHE: RefCell<Cache> = RefCell::default();
+}
+
+/// Statistics of the [BlurCache]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BlurCacheStats {
+    /// Lookups which found a texture
+    pub hits: u64,
+    /// Lookups which had to blur the content
+    pub misses: u64,
+    /// Textures removed to fit into the budget
+    pub evictions: u64,
+    /// Amount of cached textures
+    pub entries: usize,
+    /// Memory used by the cached textures in bytes
+    pub memory: usize,
+    /// Memory budget in bytes
+    pub budget: usize,
+}
+
+/// Cache of blurred textures shared by all [SpoilerOverlay](crate::SpoilerOverlay) instances
+///
+/// Spoilers whose child is a [gtk::Picture] share the blurred textures
+/// of the same paintable, size, scale and blur settings.
+/// Least recently used textures are evicted when the cache exceeds its memory budget.
+///
+/// GTK objects live in the main thread, so the cache is shared by the whole process.
+///
+/// # Example
+/// ```no_run
+/// origami::BlurCache::set_budget(16 * 1024 * 1024);
+///
+/// let stats = origami::BlurCache::stats();
+/// println!("{} hits, {} misses", stats.hits, stats.misses);
+/// ```
+pub struct BlurCache;
+
+impl BlurCache {
+    pub fn stats() -> BlurCacheStats {
+        CACHE.with(|cache| {
+            let cache = cache.borrow();
+
+            BlurCacheStats {
+                entries: cache.entries.len(),
+                memory: cache.memory(),
+                budget: cache.budget,
+                ..cache.stats
+            }
+        })
+    }
+
+    /// Memory budget in bytes, 64 MiB by default
+    pub fn budget() -> usize {
+        CACHE.with(|cache| cache.borrow().budget)
+    }
+
+    /// Sets the memory budget in bytes and evicts textures which don't fit
+    pub fn set_budget(budget: usize) {
+        CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            cache.budget = budget;
+            cache.evict(0);
+        })
+    }
+
+    /// Removes all textures, spoilers keep textures they display
+    pub fn clear() {
+        CACHE.with(|cache| cache.borrow_mut().entries.clear())
+    }
+
+    pub(crate) fn lookup(key: &BlurKey) -> Option<gdk::Texture> {
+        CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            cache.prune();
+            cache.clock += 1;
+
+            let clock = cache.clock;
+            let texture = cache
+                .entries
+                .iter_mut()
+                .find(|entry| &entry.key == key)
+                .map(|entry| {
+                    entry.last_used = clock;
+                    entry.texture.clone()
+                });
+
+            if texture.is_some() {
+                cache.stats.hits += 1;
+            } else {
+                cache.stats.misses += 1;
+            }
+
+            texture
+        })
+    }
+
+    pub(crate) fn insert(key: BlurKey, content: &gdk::Paintable, texture: &gdk::Texture) {
+        CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+
+            let memory = texture_memory(texture);
+
+            if memory > cache.budget {
+                return;
+            }
+
+            cache.prune();
+            cache.entries.retain(|entry| entry.key != key);
+            cache.evict(memory);
+            cache.clock += 1;
+
+            let entry = Entry {
+                key,
+                content: content.downgrade(),
+                texture: texture.clone(),
+                last_used: cache.clock,
+            };
+
+            cache.entries.push(entry);
+        })
+    }
+
+    /// Removes outdated textures of the paintable
+    pub(crate) fn forget(content: &gdk::Paintable) {
+        CACHE.with(|cache| {
+            cache
+                .borrow_mut()
+                .entries
+                .retain(|entry| entry.content.upgrade().as_ref() != Some(content));
+        })
+    }
+}
+
+fn texture_memory(texture: &gdk::Texture) -> usize {
+    texture.width() as usize * texture.height() as usize * 4
+}
+
+#[cfg(test)]
+mod tests {
+    // Every test runs in its own thread, so it gets an empty cache
+
+    use super::*;
+
+    fn texture(size: i32) -> gdk::Texture {
+        let stride = size as usize * 4;
+        let bytes = glib::Bytes::from_owned(vec![0; stride * size as usize]);
+
+        gdk::MemoryTexture::new(size, size, gdk::MemoryFormat::R8g8b8a8, &bytes, stride).upcast()
+    }
+
+    fn key(content: &gdk::Texture, settings: u64) -> BlurKey {
+        BlurKey {
+            content: content.as_ptr() as usize,
+            size: (16, 16, 1),
+            settings,
+        }
+    }
+
+    fn insert(content: &gdk::Texture, settings: u64) {
+        BlurCache::insert(key(content, settings), content.upcast_ref(), &texture(16));
+    }
+
+    fn contains(content: &gdk::Texture, settings: u64) -> bool {
+        BlurCache::lookup(&key(content, settings)).is_some()
+    }
+
+    /// Memory of a 16x16 texture
+    const ENTRY_MEMORY: usize = 16 * 16 * 4;
+
+    #[test]
+    fn least_recently_used_is_evicted() {
+        BlurCache::set_budget(3 * ENTRY_MEMORY);
+
+        let content = texture(1);
+        insert(&content, 0);
+        insert(&content, 1);
+        insert(&content, 2);
+
+        // The second entry becomes the least recently used
+        assert!(contains(&content, 0));
+
+        insert(&content, 3);
+
+        assert!(contains(&content, 0));
+        assert!(!contains(&content, 1));
+        assert!(contains(&content, 2));
+        assert!(contains(&content, 3));
+        assert_eq!(BlurCache::stats().evictions, 1);
+    }
+
+    #[test]
+    fn budget_is_respected() {
+        let content = texture(1);
+        insert(&content, 0);
+        insert(&content, 1);
+        insert(&content, 2);
+
+        BlurCache::set_budget(2 * ENTRY_MEMORY);
+
+        let stats = BlurCache::stats();
+        assert_eq!(stats.entries, 2);
+        assert_eq!(stats.memory, 2 * ENTRY_MEMORY);
+        assert_eq!(stats.budget, 2 * ENTRY_MEMORY);
+        assert!(!contains(&content, 0));
+
+        // Textures bigger than the whole budget aren't cached
+        BlurCache::insert(key(&content, 3), content.upcast_ref(), &texture(64));
+        assert!(!contains(&content, 3));
+        assert_eq!(BlurCache::stats().entries, 2);
+    }
+
+    #[test]
+    fn forget_removes_only_the_content() {
+        let first = texture(1);
+        let second = texture(1);
+        insert(&first, 0);
+        insert(&first, 1);
+        insert(&second, 0);
+
+        BlurCache::forget(first.upcast_ref());
+
+        assert_eq!(BlurCache::stats().entries, 1);
+        assert!(!contains(&first, 0));
+        assert!(!contains(&first, 1));
+        assert!(contains(&second, 0));
+    }
+
+    #[test]
+    fn destroyed_content_is_pruned() {
+        let first = texture(1);
+        let second = texture(1);
+        insert(&first, 0);
+        insert(&second, 0);
+
+        drop(first);
+
+        assert!(contains(&second, 0));
+        assert_eq!(BlurCache::stats().entries, 1);
+    }
+
+    #[test]
+    fn stats_count_lookups() {
+        let content = texture(1);
+
+        assert!(!contains(&content, 0));
+        insert(&content, 0);
+        assert!(contains(&content, 0));
+        assert!(contains(&content, 0));
+
+        let stats = BlurCache::stats();
+        assert_eq!(stats.hits, 2);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.evictions, 0);
+        assert_eq!(stats.entries, 1);
+        assert_eq!(stats.memory, ENTRY_MEMORY);
+        assert_eq!(stats.budget, DEFAULT_BUDGET);
+
+        BlurCache::clear();
+        assert_eq!(BlurCache::stats().entries, 0);
+    }
+}
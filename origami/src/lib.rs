@@ -1,19 +1,38 @@
 //! [Paper Plane](https://github.com/paper-plane-developers/paper-plane) related set of gtk widgets that can be usable outside of it.
 
+mod animation_driver;
+mod blur_cache;
 mod loading_indicator;
 mod shimmer_effect;
+mod spoiler_label;
 mod spoiler_overlay;
+mod spoiler_paintable;
+mod time_source;
 
+pub use animation_driver::AnimationDriver;
+pub use blur_cache::{BlurCache, BlurCacheStats};
 use gtk::prelude::StaticType;
 pub use loading_indicator::LoadingIndicator;
 pub use shimmer_effect::ShimmerEffect;
-pub use spoiler_overlay::SpoilerOverlay;
+pub use spoiler_label::SpoilerLabel;
+pub use spoiler_overlay::{
+    BlurBackend, RevealMode, RevealTransition, SpoilerGroup, SpoilerOverlay,
+};
+pub use spoiler_paintable::SpoilerPaintable;
+pub use time_source::{FrameClockTime, ManualClock, TimeSource};
 
 /// Registers all library types.
 ///
 /// Expected to be called in the main function
 pub fn init() {
+    AnimationDriver::static_type();
     LoadingIndicator::static_type();
     ShimmerEffect::static_type();
+    SpoilerLabel::static_type();
     SpoilerOverlay::static_type();
+    SpoilerGroup::static_type();
+    SpoilerPaintable::static_type();
+    BlurBackend::static_type();
+    RevealMode::static_type();
+    RevealTransition::static_type();
 }
@@ -1,16 +1,18 @@
-use gtk::glib;
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
+use gtk::{gdk, glib, graphene};
+
+use crate::animation_driver::Ticker;
 
 mod imp {
     use super::*;
-    use gtk::graphene;
     use std::cell::Cell;
 
-    #[derive(Debug, Default, glib::Properties)]
+    #[derive(Default, glib::Properties)]
     #[properties(wrapper_type = super::LoadingIndicator)]
     pub struct LoadingIndicator {
         pub(super) start_time: Cell<i64>,
+        pub(super) ticker: Ticker,
 
         #[property(get, set, minimum = 0.0, maximum = 1.0)]
         pub(super) progress: Cell<f64>,
@@ -31,10 +33,10 @@ mod imp {
         fn constructed(&self) {
             self.obj().connect_visible_notify(|widget| {
                 if widget.is_visible() {
-                    widget.imp().start_time.set(widget.time());
-                    widget.add_tick_callback(|widget, _clock| {
+                    widget.imp().start_time.set(widget.imp().ticker.time());
+                    widget.imp().ticker.start(widget, |widget| {
                         widget.queue_draw();
-                        Continue(widget.is_visible())
+                        widget.is_visible()
                     });
                 }
             });
@@ -59,6 +61,16 @@ mod imp {
             self.obj().notify("visible");
         }
 
+        fn map(&self) {
+            self.parent_map();
+            self.ticker.update(&*self.obj());
+        }
+
+        fn unmap(&self) {
+            self.parent_unmap();
+            self.ticker.update(&*self.obj());
+        }
+
         fn measure(&self, _orientation: gtk::Orientation, for_size: i32) -> (i32, i32, i32, i32) {
             if for_size < 0 {
                 (0, 32, -1, -1)
@@ -72,28 +84,15 @@ mod imp {
             let size = widget.width() as f32;
             let bounds = graphene::Rect::new(0.0, 0.0, size, size);
 
-            let context = snapshot.append_cairo(&bounds);
-            let color = widget.color();
-            context.set_source_rgba(
-                color.red() as _,
-                color.green() as _,
-                color.blue() as _,
-                color.alpha() as _,
-            );
-            let half_size = size as f64 / 2.0;
-
             let pi = std::f64::consts::PI;
 
-            context.set_line_width(2.0);
-
-            let time = widget.time() - self.start_time.get();
+            let time = self.ticker.time() - self.start_time.get();
             let shift = (time as f64 / 300000.0) % (2.0 * pi);
 
             let start = shift - 0.5 * pi;
             let diff = self.progress.get().max(0.04) * 2.0 * pi;
 
-            context.arc(half_size, half_size, half_size - 2.0, start, start + diff);
-            context.stroke().unwrap();
+            append_arc(snapshot, &bounds, &widget.color(), start, diff);
         }
     }
 }
@@ -108,11 +107,33 @@ glib::wrapper! {
         @extends gtk::Widget;
 }
 
-impl LoadingIndicator {
-    fn time(&self) -> i64 {
-        self.frame_clock()
-            .and_then(|clk| clk.current_timings())
-            .map(|t| t.frame_time())
-            .unwrap_or_default()
-    }
+/// Draws the arc the same way as [LoadingIndicator] does
+///
+/// Angles are measured in radians
+pub(crate) fn append_arc(
+    snapshot: &gtk::Snapshot,
+    bounds: &graphene::Rect,
+    color: &gdk::RGBA,
+    start: f64,
+    sweep: f64,
+) {
+    let context = snapshot.append_cairo(bounds);
+    context.set_source_rgba(
+        color.red() as _,
+        color.green() as _,
+        color.blue() as _,
+        color.alpha() as _,
+    );
+
+    let half_size = bounds.width() as f64 / 2.0;
+
+    context.set_line_width(2.0);
+    context.arc(
+        bounds.x() as f64 + half_size,
+        bounds.y() as f64 + half_size,
+        half_size - 2.0,
+        start,
+        start + sweep,
+    );
+    context.stroke().unwrap();
 }
@@ -3,6 +3,8 @@ use adw::subclass::prelude::*;
 use glib::clone;
 use gtk::{glib, graphene, gsk};
 
+use crate::animation_driver::Ticker;
+
 const GRADIENT_WIDTH: f32 = 256.0;
 const GRADIENT_PIXELS_PER_SEC: f32 = 100.0;
 
@@ -15,6 +17,7 @@ mod imp {
     pub struct ShimmerEffect {
         #[property(get, set)]
         pub(super) playing: Cell<bool>,
+        pub(super) ticker: Ticker,
     }
 
     #[glib::object_subclass]
@@ -51,9 +54,9 @@ mod imp {
 
             self.obj().connect_playing_notify(|widget| {
                 if widget.playing() {
-                    widget.add_tick_callback(|widget, _clock| {
+                    widget.imp().ticker.start(widget, |widget| {
                         widget.queue_draw();
-                        Continue(widget.playing())
+                        widget.playing()
                     });
                 }
             });
@@ -61,6 +64,16 @@ mod imp {
     }
 
     impl WidgetImpl for ShimmerEffect {
+        fn map(&self) {
+            self.parent_map();
+            self.ticker.update(&*self.obj());
+        }
+
+        fn unmap(&self) {
+            self.parent_unmap();
+            self.ticker.update(&*self.obj());
+        }
+
         fn snapshot(&self, snapshot: &gtk::Snapshot) {
             if !self.playing.get() {
                 self.parent_snapshot(snapshot);
@@ -80,7 +93,9 @@ mod imp {
 
             let win_bounds = window.compute_bounds(self.obj().as_ref()).unwrap();
 
-            let time_secs = std::time::Duration::from_micros(widget.time() as u64).as_secs_f32();
+            // Time of the window keeps all shimmers in sync
+            let time_secs =
+                std::time::Duration::from_micros(self.ticker.clock_time() as u64).as_secs_f32();
 
             let shift = time_secs * GRADIENT_PIXELS_PER_SEC % GRADIENT_WIDTH;
 
@@ -121,12 +136,3 @@ glib::wrapper! {
     pub struct ShimmerEffect(ObjectSubclass<imp::ShimmerEffect>)
         @extends adw::Bin, gtk::Widget;
 }
-
-impl ShimmerEffect {
-    fn time(&self) -> i64 {
-        self.frame_clock()
-            .and_then(|clk| clk.current_timings())
-            .map(|t| t.frame_time())
-            .unwrap_or_default()
-    }
-}
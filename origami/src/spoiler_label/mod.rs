@@ -0,0 +1,399 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use glib::clone;
+use gtk::{gdk, glib, graphene, gsk, pango};
+use std::cell::Cell;
+use std::ops::Range;
+use std::rc::Rc;
+
+use crate::animation_driver::Ticker;
+use crate::spoiler_overlay::{append_reveal_gradient, particles_time, reveal_radius, Particles};
+
+/// Hidden range of the text
+pub(super) struct Spoiler {
+    range: Range<usize>,
+    hidden: Cell<bool>,
+    reveal_progress: Rc<Cell<f32>>,
+    click_point: Cell<Option<(f32, f32)>>,
+    animation: adw::TimedAnimation,
+}
+
+mod imp {
+    use gtk::glib::once_cell::unsync::OnceCell;
+
+    use super::*;
+    use std::cell::RefCell;
+
+    #[derive(Default, glib::Properties)]
+    #[properties(wrapper_type = super::SpoilerLabel)]
+    pub struct SpoilerLabel {
+        pub(super) start_time: Cell<i64>,
+        pub(super) ticker: Ticker,
+
+        pub(super) spoilers: RefCell<Vec<Spoiler>>,
+
+        #[property(get)]
+        pub(super) label: OnceCell<gtk::Label>,
+
+        #[property(get, set = Self::set_text)]
+        pub(super) text: RefCell<String>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for SpoilerLabel {
+        const NAME: &'static str = "OriSpoilerLabel";
+        type Type = super::SpoilerLabel;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.set_layout_manager_type::<gtk::BinLayout>();
+        }
+    }
+
+    impl ObjectImpl for SpoilerLabel {
+        fn properties() -> &'static [glib::ParamSpec] {
+            Self::derived_properties()
+        }
+
+        fn property(&self, id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            self.derived_property(id, pspec)
+        }
+
+        fn set_property(&self, id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            self.derived_set_property(id, value, pspec)
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let widget = self.obj();
+
+            let label = gtk::Label::builder()
+                .wrap(true)
+                .xalign(0.0)
+                .label(&*self.text.borrow())
+                .build();
+
+            label.set_parent(&*widget);
+            self.label.set(label).unwrap();
+
+            let controller = gtk::GestureClick::builder()
+                .button(1)
+                .propagation_phase(gtk::PropagationPhase::Capture)
+                .build();
+
+            controller.connect_pressed(clone!(@weak widget => move |gesture, _button, x, y| {
+                if widget.imp().reveal_at(x as f32, y as f32) {
+                    gesture.set_state(gtk::EventSequenceState::Claimed);
+                }
+            }));
+
+            widget.add_controller(controller);
+        }
+
+        fn dispose(&self) {
+            if let Some(label) = self.label.get() {
+                label.unparent();
+            }
+        }
+    }
+
+    impl WidgetImpl for SpoilerLabel {
+        fn realize(&self) {
+            self.parent_realize();
+            self.start_ticking();
+        }
+
+        fn map(&self) {
+            self.parent_map();
+            self.ticker.update(&*self.obj());
+        }
+
+        fn unmap(&self) {
+            self.parent_unmap();
+            self.ticker.update(&*self.obj());
+        }
+
+        fn snapshot(&self, snapshot: &gtk::Snapshot) {
+            let widget = self.obj();
+            let label = self.label.get().unwrap();
+
+            let spoilers = self.spoilers.borrow();
+
+            let areas: Vec<_> = spoilers
+                .iter()
+                .filter(|spoiler| spoiler.reveal_progress.get() < 1.0)
+                .map(|spoiler| (spoiler, self.range_rects(&spoiler.range)))
+                .filter(|(_, rects)| !rects.is_empty())
+                .collect();
+
+            if areas.is_empty() {
+                widget.snapshot_child(label, snapshot);
+                return;
+            }
+
+            // Hide glyphs of the spoilers
+            snapshot.push_mask(gsk::MaskMode::InvertedAlpha);
+            for rect in areas.iter().flat_map(|(_, rects)| rects) {
+                snapshot.append_color(&gdk::RGBA::BLACK, rect);
+            }
+            snapshot.pop();
+            widget.snapshot_child(label, snapshot);
+            snapshot.pop();
+
+            let time = particles_time(self.ticker.time() - self.start_time.get());
+            let color = widget.color();
+            let particles = Particles {
+                scale: widget.scale_factor() as f32,
+                ..Default::default()
+            };
+
+            for (spoiler, rects) in &areas {
+                let area = rects
+                    .iter()
+                    .skip(1)
+                    .fold(rects[0].clone(), |area, rect| area.union(rect));
+
+                let (x, y) = spoiler
+                    .click_point
+                    .get()
+                    .unwrap_or((area.center().x(), area.center().y()));
+
+                let center = graphene::Point::new(x, y);
+
+                let progress = spoiler.reveal_progress.get();
+                let radius = reveal_radius(&area, &center, progress);
+
+                for rect in rects {
+                    snapshot.push_clip(rect);
+
+                    if radius > 0.0 {
+                        // Revealed part of the glyphs
+                        snapshot.push_mask(gsk::MaskMode::Alpha);
+                        append_reveal_gradient(snapshot, rect, &center, radius, progress);
+                        snapshot.pop();
+                        widget.snapshot_child(label, snapshot);
+                        snapshot.pop();
+
+                        snapshot.push_mask(gsk::MaskMode::InvertedAlpha);
+                        append_reveal_gradient(snapshot, rect, &center, radius, progress);
+                        snapshot.pop();
+                    }
+
+                    particles.render(snapshot, rect, time, &color);
+
+                    if radius > 0.0 {
+                        snapshot.pop();
+                    }
+
+                    snapshot.pop();
+                }
+            }
+        }
+    }
+
+    impl SpoilerLabel {
+        fn set_text(&self, text: String) {
+            if let Some(label) = self.label.get() {
+                label.set_label(&text);
+            }
+
+            self.text.replace(text);
+        }
+
+        pub(super) fn start_ticking(&self) {
+            let widget = self.obj();
+
+            if self.ticker.is_running() || !widget.has_hidden_spoilers() {
+                return;
+            }
+
+            self.start_time.set(self.ticker.time());
+
+            self.ticker.start(&*widget, |widget| {
+                widget.queue_draw();
+                widget.has_hidden_spoilers()
+            });
+        }
+
+        /// Reveals the hidden spoiler under the point
+        ///
+        /// Returns `true` if there was a spoiler
+        fn reveal_at(&self, x: f32, y: f32) -> bool {
+            let point = graphene::Point::new(x, y);
+
+            let spoilers = self.spoilers.borrow();
+
+            let spoiler = spoilers.iter().find(|spoiler| {
+                spoiler.hidden.get()
+                    && self
+                        .range_rects(&spoiler.range)
+                        .iter()
+                        .any(|rect| rect.contains_point(&point))
+            });
+
+            if let Some(spoiler) = spoiler {
+                spoiler.hidden.set(false);
+                spoiler.click_point.set(Some((x, y)));
+                spoiler.animation.set_reverse(false);
+                spoiler.animation.play();
+                true
+            } else {
+                false
+            }
+        }
+
+        /// Rectangles covering glyphs of the range, one per line
+        fn range_rects(&self, range: &Range<usize>) -> Vec<graphene::Rect> {
+            let widget = self.obj();
+            let label = self.label.get().unwrap();
+
+            let (offset_x, offset_y) = label.layout_offsets();
+            let Some(offset) = label.compute_point(
+                &*widget,
+                &graphene::Point::new(offset_x as f32, offset_y as f32),
+            ) else {
+                return Vec::new();
+            };
+
+            let scale = pango::SCALE as f32;
+
+            let mut rects: Vec<graphene::Rect> = Vec::new();
+            let mut iter = label.layout().iter();
+
+            loop {
+                if range.contains(&(iter.index() as usize)) {
+                    let extents = iter.char_extents();
+
+                    // Width is negative for RTL text
+                    let x = extents.x().min(extents.x() + extents.width());
+
+                    let rect = graphene::Rect::new(
+                        offset.x() + x as f32 / scale,
+                        offset.y() + extents.y() as f32 / scale,
+                        extents.width().abs() as f32 / scale,
+                        extents.height() as f32 / scale,
+                    );
+
+                    match rects.last_mut() {
+                        Some(last) if last.y() == rect.y() && last.height() == rect.height() => {
+                            *last = last.union(&rect);
+                        }
+                        _ => rects.push(rect),
+                    }
+                }
+
+                if !iter.next_char() {
+                    break;
+                }
+            }
+
+            rects
+        }
+    }
+}
+
+glib::wrapper! {
+    #[doc(alias = "OriSpoilerLabel")]
+    /// Label with hidden parts of the text
+    ///
+    /// Spoilers are drawn with the same particles as [SpoilerOverlay](crate::SpoilerOverlay)
+    /// and every one of them is revealed separately after a click
+    ///
+    /// # Properties
+    ///
+    /// * Text: [String].
+    /// Text of the label.
+    ///
+    /// * Label: readonly [gtk::Label].
+    /// Label used to display the text, it can be used to customize wrapping, alignment, etc.
+    ///
+    /// # Example
+    /// ```no_run
+    /// let label = origami::SpoilerLabel::new("The butler did it");
+    /// label.set_spoilers(&[4..10]);
+    /// ```
+    pub struct SpoilerLabel(ObjectSubclass<imp::SpoilerLabel>)
+        @extends gtk::Widget, @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl SpoilerLabel {
+    pub fn new(text: &str) -> Self {
+        glib::Object::builder().property("text", text).build()
+    }
+
+    /// Hides byte ranges of the text
+    ///
+    /// Replaces previous spoilers, all new spoilers are hidden
+    pub fn set_spoilers(&self, ranges: &[Range<usize>]) {
+        let spoilers = ranges
+            .iter()
+            .map(|range| {
+                let reveal_progress = Rc::new(Cell::new(0.0));
+
+                let target = adw::CallbackAnimationTarget::new(
+                    clone!(@weak self as widget, @strong reveal_progress => move |progress| {
+                        reveal_progress.set(progress as f32);
+                        widget.queue_draw();
+                    }),
+                );
+
+                let animation = adw::TimedAnimation::builder()
+                    .widget(self)
+                    .value_from(0.0)
+                    .value_to(1.0)
+                    .duration(1000)
+                    .easing(adw::Easing::EaseInOutCubic)
+                    .target(&target)
+                    .repeat_count(1)
+                    .build();
+
+                Spoiler {
+                    range: range.clone(),
+                    hidden: Cell::new(true),
+                    reveal_progress,
+                    click_point: Default::default(),
+                    animation,
+                }
+            })
+            .collect();
+
+        self.imp().spoilers.replace(spoilers);
+        self.imp().start_ticking();
+        self.queue_draw();
+    }
+
+    /// Byte ranges of the spoilers
+    pub fn spoilers(&self) -> Vec<Range<usize>> {
+        self.imp()
+            .spoilers
+            .borrow()
+            .iter()
+            .map(|spoiler| spoiler.range.clone())
+            .collect()
+    }
+
+    /// Hides revealed spoilers back with animation
+    pub fn hide_spoilers(&self) {
+        for spoiler in self.imp().spoilers.borrow().iter() {
+            if spoiler.hidden.replace(true) {
+                continue;
+            }
+
+            spoiler.click_point.set(None);
+            spoiler.animation.set_reverse(true);
+            spoiler.animation.play();
+        }
+
+        self.imp().start_ticking();
+    }
+
+    /// Whether any spoiler isn't fully revealed
+    fn has_hidden_spoilers(&self) -> bool {
+        self.imp()
+            .spoilers
+            .borrow()
+            .iter()
+            .any(|spoiler| spoiler.reveal_progress.get() < 1.0)
+    }
+}
@@ -0,0 +1,102 @@
+//! Futures resolved by the reveal animation
+
+use adw::prelude::*;
+use gtk::glib;
+use std::cell::{Cell, RefCell};
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+
+#[derive(Default)]
+struct State {
+    finished: Cell<Option<bool>>,
+    waker: RefCell<Option<Waker>>,
+}
+
+impl State {
+    fn resolve(&self, finished: bool) {
+        if self.finished.get().is_none() {
+            self.finished.set(Some(finished));
+        }
+
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Resolves when the animation to the `target` value stops
+///
+/// Output is `true` when the animation reached the `target`
+/// and `false` when it was paused or restarted towards another value
+pub(super) struct AnimationFuture {
+    animation: adw::TimedAnimation,
+    target: f64,
+    state: Rc<State>,
+    handlers: Vec<glib::SignalHandlerId>,
+}
+
+impl AnimationFuture {
+    /// Expected to be created before the animation is played,
+    /// because an animation without duration finishes immediately
+    pub(super) fn new(animation: &adw::TimedAnimation, target: f64) -> Self {
+        let state = Rc::new(State::default());
+
+        // Playing animation is restarted without changing its state,
+        // so the interruption is noticed by the new target
+        let value_handler = animation.connect_value_to_notify({
+            let state = state.clone();
+            move |animation| {
+                if animation.value_to() != target {
+                    state.resolve(false);
+                }
+            }
+        });
+
+        let state_handler = animation.connect_state_notify({
+            let state = state.clone();
+            move |animation| match animation.state() {
+                adw::AnimationState::Finished => state.resolve(animation.value_to() == target),
+                adw::AnimationState::Paused => state.resolve(false),
+                _ => {}
+            }
+        });
+
+        Self {
+            animation: animation.clone(),
+            target,
+            state,
+            handlers: vec![value_handler, state_handler],
+        }
+    }
+}
+
+impl Future for AnimationFuture {
+    type Output = bool;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<bool> {
+        if let Some(finished) = self.state.finished.get() {
+            return Poll::Ready(finished);
+        }
+
+        // The animation wasn't played, e.g. the reveal was cancelled
+        // or the overlay is already in the requested state
+        match self.animation.state() {
+            adw::AnimationState::Playing => {
+                self.state.waker.replace(Some(cx.waker().clone()));
+                Poll::Pending
+            }
+            adw::AnimationState::Paused => Poll::Ready(false),
+            _ => Poll::Ready(self.animation.value_to() == self.target),
+        }
+    }
+}
+
+impl Drop for AnimationFuture {
+    fn drop(&mut self) {
+        for handler in self.handlers.drain(..) {
+            self.animation.disconnect(handler);
+        }
+    }
+}
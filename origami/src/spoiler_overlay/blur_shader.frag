@@ -1,6 +1,11 @@
 // Based on https://www.shadertoy.com/view/Xltfzj
 uniform sampler2D u_texture1;
 
+// Blur radius in pixels
+uniform float u_radius;
+// Amount of samples taken along every direction
+uniform float u_quality;
+
 void mainImage(out vec4 fragColor,
     in vec2 fragCoord,
     in vec2 resolution,
@@ -8,15 +13,16 @@ void mainImage(out vec4 fragColor,
     float Pi = 6.28318530718; // Pi*2
     
     // GAUSSIAN BLUR SETTINGS {{{
-    float Directions = 32.0; // BLUR DIRECTIONS (Default 16.0 - More is better but slower)
-    float Quality = 16.0; // BLUR QUALITY (Default 4.0 - More is better but slower)
-    float Size = 128.0; // BLUR SIZE (Radius)
+    float Quality = max(u_quality, 1.0); // BLUR QUALITY (More is better but slower)
+    float Directions = Quality * 2.0; // BLUR DIRECTIONS (More is better but slower)
+    float Size = u_radius; // BLUR SIZE (Radius)
     // GAUSSIAN BLUR SETTINGS }}}
    
     vec2 Radius = Size/resolution.xy;
     
     // Pixel colour
     vec4 Color = texture(u_texture1, uv);
+    float Samples = 1.0;
     
     // Blur calculations
     for( float d=0.0; d<Pi; d+=Pi/Directions)
@@ -25,10 +31,11 @@ void mainImage(out vec4 fragColor,
         {
             vec2 coord = uv+vec2(cos(d),sin(d))*Radius*i;
 			Color += texture(u_texture1, clamp(coord, vec2(0), resolution));		
+            Samples += 1.0;
         }
     }
     
     // Output to screen
-    Color /= Quality * Directions - 15.0;
+    Color /= Samples;
     fragColor =  Color;
-}
\ No newline at end of file
+}
@@ -0,0 +1,64 @@
+//! Tracks contents of the child to refresh the blur when they change
+
+use gtk::glib;
+use gtk::prelude::*;
+
+/// Watches paintables of the pictures inside the child
+///
+/// Signal handlers are disconnected on drop
+pub(super) struct ContentWatch {
+    handlers: Vec<(glib::Object, glib::SignalHandlerId)>,
+}
+
+impl ContentWatch {
+    /// `on_paintable_changed` is called when a picture gets another paintable,
+    /// so the watch should be recreated,
+    /// `on_contents_changed` is called when any watched paintable redraws
+    pub(super) fn new(
+        child: &gtk::Widget,
+        on_paintable_changed: impl Fn() + Clone + 'static,
+        on_contents_changed: impl Fn() + Clone + 'static,
+    ) -> Self {
+        let mut handlers = Vec::new();
+
+        for picture in pictures(child) {
+            let on_paintable_changed = on_paintable_changed.clone();
+            let handler = picture.connect_paintable_notify(move |_| on_paintable_changed());
+            handlers.push((picture.clone().upcast(), handler));
+
+            if let Some(paintable) = picture.paintable() {
+                let on_contents_changed = on_contents_changed.clone();
+                let handler = paintable.connect_invalidate_contents(move |_| on_contents_changed());
+                handlers.push((paintable.upcast(), handler));
+            }
+        }
+
+        Self { handlers }
+    }
+}
+
+impl Drop for ContentWatch {
+    fn drop(&mut self) {
+        for (object, handler) in self.handlers.drain(..) {
+            object.disconnect(handler);
+        }
+    }
+}
+
+/// Pictures inside the widget tree
+fn pictures(widget: &gtk::Widget) -> Vec<gtk::Picture> {
+    let mut pictures = Vec::new();
+
+    if let Some(picture) = widget.downcast_ref::<gtk::Picture>() {
+        pictures.push(picture.clone());
+    }
+
+    let mut child = widget.first_child();
+
+    while let Some(widget) = child {
+        pictures.extend(self::pictures(&widget));
+        child = widget.next_sibling();
+    }
+
+    pictures
+}
@@ -0,0 +1,152 @@
+//! Software blur used when the blur shader isn't available
+//!
+//! Three box blur passes are close enough to the gaussian blur,
+//! and every pass takes the same time regardless of the radius.
+
+use gtk::prelude::*;
+use gtk::{gdk, glib};
+
+#[cfg(target_endian = "little")]
+const MEMORY_FORMAT: gdk::MemoryFormat = gdk::MemoryFormat::B8g8r8a8Premultiplied;
+#[cfg(target_endian = "big")]
+const MEMORY_FORMAT: gdk::MemoryFormat = gdk::MemoryFormat::A8r8g8b8Premultiplied;
+
+/// Blurs the texture
+///
+/// `radius` is measured in texture pixels
+pub(super) fn blur(texture: &gdk::Texture, radius: f32) -> gdk::Texture {
+    let width = texture.width() as usize;
+    let height = texture.height() as usize;
+    let stride = width * 4;
+
+    let mut data = vec![0; stride * height];
+    texture.download(&mut data, stride);
+
+    let radius = box_radius(radius).round() as usize;
+
+    if radius > 0 && width > 0 && height > 0 {
+        let mut buffer = vec![0; data.len()];
+
+        for _ in 0..3 {
+            box_blur(&data, &mut buffer, width, height, 4, stride, radius);
+            box_blur(&buffer, &mut data, height, width, stride, 4, radius);
+        }
+    }
+
+    let bytes = glib::Bytes::from_owned(data);
+
+    gdk::MemoryTexture::new(width as i32, height as i32, MEMORY_FORMAT, &bytes, stride).upcast()
+}
+
+/// Radius of a single box blur pass giving the blur of the same size as the shader
+///
+/// Zero radius disables the blur like in other backends
+fn box_radius(radius: f32) -> f32 {
+    if radius > 0.0 {
+        (radius * 0.5).max(1.0)
+    } else {
+        0.0
+    }
+}
+
+/// Blurs every line of the image with the sliding window
+///
+/// `step` is a distance between neighbour pixels of the line
+/// and `line_step` is a distance between lines,
+/// so the same function works for both directions
+fn box_blur(
+    src: &[u8],
+    dst: &mut [u8],
+    len: usize,
+    lines: usize,
+    step: usize,
+    line_step: usize,
+    radius: usize,
+) {
+    let window = (radius * 2 + 1) as u32;
+    let last = len - 1;
+
+    for line in 0..lines {
+        let start = line * line_step;
+
+        for channel in 0..4 {
+            let pixel = |i: usize| src[start + i.min(last) * step + channel] as u32;
+
+            // Pixels before the line start are clamped to the first one
+            let mut sum = pixel(0) * radius as u32 + (0..=radius).map(pixel).sum::<u32>();
+
+            for i in 0..len {
+                dst[start + i * step + channel] = (sum / window) as u8;
+
+                sum += pixel(i + radius + 1);
+                sum -= pixel(i.saturating_sub(radius));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Line of pixels with the same value in every channel
+    fn line(values: &[u8]) -> Vec<u8> {
+        values.iter().flat_map(|&value| [value; 4]).collect()
+    }
+
+    #[test]
+    fn constant_image_is_unchanged() {
+        let (width, height) = (7, 5);
+        let stride = width * 4;
+        let src = vec![100; stride * height];
+        let mut dst = vec![0; src.len()];
+
+        box_blur(&src, &mut dst, width, height, 4, stride, 3);
+        assert_eq!(dst, src);
+
+        box_blur(&src, &mut dst, height, width, stride, 4, 3);
+        assert_eq!(dst, src);
+    }
+
+    #[test]
+    fn edges_are_clamped() {
+        let src = line(&[0, 30, 60, 90]);
+        let mut dst = vec![0; src.len()];
+
+        box_blur(&src, &mut dst, 4, 1, 4, 16, 1);
+
+        assert_eq!(dst, line(&[10, 30, 60, 80]));
+    }
+
+    #[test]
+    fn radius_larger_than_line() {
+        let src = line(&[0, 90]);
+        let mut dst = vec![0; src.len()];
+
+        box_blur(&src, &mut dst, 2, 1, 4, 8, 2);
+
+        // Windows are [0, 0, 0, 90, 90] and [0, 0, 90, 90, 90]
+        assert_eq!(dst, line(&[36, 54]));
+    }
+
+    #[test]
+    fn columns_are_blurred() {
+        // Two columns and three rows
+        let (width, height) = (2, 3);
+        let stride = width * 4;
+        let src = line(&[0, 90, 30, 90, 60, 90]);
+        let mut dst = vec![0; src.len()];
+
+        box_blur(&src, &mut dst, height, width, stride, 4, 1);
+
+        assert_eq!(dst, line(&[10, 90, 30, 90, 50, 90]));
+    }
+
+    #[test]
+    fn zero_radius_disables_blur() {
+        assert_eq!(box_radius(0.0), 0.0);
+        assert_eq!(box_radius(-1.0), 0.0);
+        assert_eq!(box_radius(0.5), 1.0);
+        assert_eq!(box_radius(16.0), 8.0);
+    }
+}
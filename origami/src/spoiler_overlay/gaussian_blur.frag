@@ -0,0 +1,36 @@
+// Single pass of the separable gaussian blur,
+// the content is expected to be downscaled before blurring
+uniform sampler2D u_texture1;
+
+// Blur radius in pixels, matches the radius of blur_shader.frag
+uniform float u_radius;
+// Direction of the pass, (1, 0) for horizontal and (0, 1) for vertical
+uniform vec2 u_direction;
+// Amount of samples taken on each side of the pixel
+uniform float u_samples;
+
+void mainImage(out vec4 fragColor,
+    in vec2 fragCoord,
+    in vec2 resolution,
+    in vec2 uv) {
+    // Deviation of the samples of blur_shader.frag,
+    // they are evenly spread over the radius in every direction
+    float sigma = max(u_radius / sqrt(6.0), 0.001);
+    float extent = 3.0 * sigma;
+
+    vec2 step = u_direction * extent / (max(u_samples, 1.0) * resolution);
+
+    vec4 color = texture(u_texture1, uv);
+    float total = 1.0;
+
+    for (float i = 1.0; i <= u_samples; i += 1.0) {
+        float x = i * extent / u_samples;
+        float weight = exp(-0.5 * x * x / (sigma * sigma));
+
+        color += texture(u_texture1, clamp(uv + step * i, vec2(0), vec2(1))) * weight;
+        color += texture(u_texture1, clamp(uv - step * i, vec2(0), vec2(1))) * weight;
+        total += 2.0 * weight;
+    }
+
+    fragColor = color / total;
+}
@@ -0,0 +1,138 @@
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::{glib, graphene};
+use std::cell::{Cell, RefCell};
+
+use super::{reveal_radius, SpoilerOverlay};
+
+mod imp {
+    use super::*;
+
+    #[derive(Default)]
+    pub struct SpoilerGroup {
+        pub(super) members: RefCell<Vec<glib::WeakRef<SpoilerOverlay>>>,
+        /// Set while the members are revealed, so they don't reveal the group again
+        pub(super) revealing: Cell<bool>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for SpoilerGroup {
+        const NAME: &'static str = "OriSpoilerGroup";
+        type Type = super::SpoilerGroup;
+    }
+
+    impl ObjectImpl for SpoilerGroup {}
+}
+
+glib::wrapper! {
+    #[doc(alias = "OriSpoilerGroup")]
+    /// Set of [SpoilerOverlay]s revealed together
+    ///
+    /// Overlays join the group with their `group` property.
+    /// When any member is revealed, all other members are revealed too,
+    /// and the radial animation spreads from the same point like a single ripple.
+    ///
+    /// Press and hold peeking isn't shared by the group.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # let overlays: Vec<origami::SpoilerOverlay> = Vec::new();
+    /// let group = origami::SpoilerGroup::new();
+    ///
+    /// for overlay in &overlays {
+    ///     overlay.set_property("group", &group);
+    /// }
+    /// ```
+    pub struct SpoilerGroup(ObjectSubclass<imp::SpoilerGroup>);
+}
+
+impl Default for SpoilerGroup {
+    fn default() -> Self {
+        glib::Object::new()
+    }
+}
+
+impl SpoilerGroup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Overlays of the group
+    pub fn members(&self) -> Vec<SpoilerOverlay> {
+        let mut members = self.imp().members.borrow_mut();
+        members.retain(|member| member.upgrade().is_some());
+        members
+            .iter()
+            .filter_map(|member| member.upgrade())
+            .collect()
+    }
+
+    /// Reveals all members from the center of the first one
+    pub fn reveal(&self) {
+        for member in self.members() {
+            member.set_hidden(false);
+        }
+    }
+
+    /// Hides all members
+    pub fn hide(&self) {
+        for member in self.members() {
+            member.set_hidden(true);
+        }
+    }
+
+    pub(super) fn add(&self, overlay: &SpoilerOverlay) {
+        self.imp().members.borrow_mut().push(overlay.downgrade());
+    }
+
+    pub(super) fn remove(&self, overlay: &SpoilerOverlay) {
+        self.imp()
+            .members
+            .borrow_mut()
+            .retain(|member| member.upgrade().is_some_and(|member| &member != overlay));
+    }
+
+    /// Reveals other members from the `center` of the `origin`
+    ///
+    /// The center is mapped into the coordinates of every member
+    /// and all of them use the same radius, so the reveal looks continuous
+    pub(super) fn reveal_from(&self, origin: &SpoilerOverlay, center: &graphene::Point) {
+        if self.imp().revealing.replace(true) {
+            return;
+        }
+
+        let members: Vec<_> = self
+            .members()
+            .into_iter()
+            .map(|member| {
+                // Members in other windows are revealed from their centers
+                let center = origin.compute_point(&member, center).unwrap_or_else(|| {
+                    graphene::Point::new(member.width() as f32 * 0.5, member.height() as f32 * 0.5)
+                });
+
+                (member, center)
+            })
+            .collect();
+
+        let radius = members
+            .iter()
+            .map(|(member, center)| {
+                let bounds =
+                    graphene::Rect::new(0.0, 0.0, member.width() as f32, member.height() as f32);
+                reveal_radius(&bounds, center, 1.0)
+            })
+            .fold(0.0, f32::max);
+
+        for (member, center) in members {
+            member.imp().group_reveal_radius.set(Some(radius));
+
+            if &member != origin && member.hidden() {
+                member.imp().reveal_center.replace(Some(center));
+                member.notify("reveal-center");
+                member.set_hidden(false);
+            }
+        }
+
+        self.imp().revealing.set(false);
+    }
+}
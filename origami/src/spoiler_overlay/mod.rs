@@ -1,37 +1,252 @@
 use adw::prelude::*;
 use adw::subclass::prelude::*;
 use glib::clone;
-use gtk::glib::once_cell::sync::Lazy;
 use gtk::{gdk, gio, glib, graphene, gsk};
+use std::collections::hash_map::DefaultHasher;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
 
-static PARTICLE_TEXTURE: Lazy<gdk::Texture> = Lazy::new(|| {
-    let bytes = glib::Bytes::from_static(include_bytes!("turbulence_2x.png"));
-    gdk::Texture::from_bytes(&bytes).unwrap()
-});
+use crate::animation_driver::Ticker;
+use crate::blur_cache::{BlurCache, BlurKey};
+use crate::loading_indicator::append_arc;
+
+mod animation_future;
+mod content_watch;
+mod cpu_blur;
+mod group;
+mod particles;
+mod transitions;
+
+use animation_future::AnimationFuture;
+use content_watch::ContentWatch;
+pub use group::SpoilerGroup;
+pub(crate) use particles::Particles;
 
 const BLUR_SHADER: &[u8] = include_bytes!("blur_shader.frag");
+const GAUSSIAN_SHADER: &[u8] = include_bytes!("gaussian_blur.frag");
+
+/// Most samples taken on each side of the pixel by a gaussian pass
+const MAX_GAUSSIAN_SAMPLES: f32 = 32.0;
+
+/// Blur radius the content is downscaled to before blurring
+///
+/// There is no reason to blur a full size texture
+/// because all the details will be lost anyway
+const DOWNSCALED_BLUR_RADIUS: f32 = 16.0;
+
+/// Duration of the full reveal animation in milliseconds
+pub(crate) const REVEAL_DURATION: u32 = 1000;
+
+/// Shortest time between automatic blur refreshes in microseconds
+pub(crate) const BLUR_REFRESH_INTERVAL: i64 = 500_000;
+
+/// Size of the `reveal-timeout` countdown ring
+const COUNTDOWN_SIZE: f32 = 24.0;
+/// Distance between the countdown ring and the corner
+const COUNTDOWN_MARGIN: f32 = 8.0;
+
+/// Scratched part of the spoiler needed to reveal the whole child
+const SCRATCH_REVEAL_COVERAGE: f32 = 0.5;
+
+/// Keys activating the hidden overlay
+const ACTIVATE_KEYS: [gdk::Key; 3] = [gdk::Key::Return, gdk::Key::KP_Enter, gdk::Key::space];
+
+/// Animation used by [SpoilerOverlay] to reveal and hide its child
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, glib::Enum)]
+#[enum_type(name = "OriRevealTransition")]
+pub enum RevealTransition {
+    /// Circle growing from the click point
+    #[default]
+    Radial,
+    /// Spoiler fades out
+    Crossfade,
+    /// Spoiler dissolves into random pieces while particles scatter outward
+    Dissolve,
+    /// Soft edge moving away from the side closest to the click point
+    Wipe,
+}
+
+/// User interaction which reveals [SpoilerOverlay]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, glib::Enum)]
+#[enum_type(name = "OriRevealMode")]
+pub enum RevealMode {
+    /// Click reveals the whole child
+    #[default]
+    Click,
+    /// Dragging clears the spoiler along the path like on a scratch card,
+    /// the whole child is revealed when the most of it is scratched
+    Scratch,
+    /// Long press or a held Enter or Space key reveals the child only while it's held
+    PressAndHold,
+    /// The child is revealed only by the app
+    Programmatic,
+}
+
+/// Method used by [SpoilerOverlay] to blur its child
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, glib::Enum)]
+#[enum_type(name = "OriBlurBackend")]
+pub enum BlurBackend {
+    /// Two passes of the gaussian [gsk::GLShader] over the downscaled child,
+    /// it falls back to [BlurBackend::BlurNode] when the renderer doesn't support shaders.
+    /// `blur-quality` has no effect, the amount of samples follows the radius
+    #[default]
+    Gaussian,
+    /// Single pass [gsk::GLShader] at full resolution, it falls back to [BlurBackend::Cpu]
+    /// when the renderer doesn't support it
+    GlShader,
+    /// [gsk::BlurNode], works on every renderer
+    BlurNode,
+    /// Blur computed on the CPU
+    Cpu,
+}
 
 mod imp {
+    use glib::subclass::Signal;
+    use gtk::glib::once_cell::sync::Lazy;
     use gtk::glib::once_cell::unsync::OnceCell;
 
     use super::*;
     use std::cell::{Cell, RefCell};
 
-    #[derive(Default, glib::Properties)]
+    #[derive(glib::Properties)]
     #[properties(wrapper_type = super::SpoilerOverlay)]
     pub struct SpoilerOverlay {
         pub(super) start_time: Cell<i64>,
-        pub(super) reveal_progress: Cell<f32>,
-        pub(super) click_point: Cell<(f32, f32)>,
+        pub(super) ticker: Ticker,
+
+        #[property(get, set = Self::set_reveal_progress, minimum = 0.0, maximum = 1.0, default_value = 1.0)]
+        pub(super) reveal_progress: Cell<f64>,
+
+        #[property(get, set = Self::set_reveal_center)]
+        pub(super) reveal_center: RefCell<Option<graphene::Point>>,
+
+        #[property(get, set = Self::set_reveal_mode, builder(RevealMode::default()))]
+        pub(super) reveal_mode: Cell<RevealMode>,
+        pub(super) peeking: Cell<bool>,
+        pub(super) scratch_strokes: RefCell<Vec<Vec<graphene::Point>>>,
 
         pub(super) shader: RefCell<Option<gsk::GLShader>>,
-        pub(super) blurred_texture_cache: RefCell<Option<(gdk::Texture, (f32, f32))>>,
+        pub(super) gaussian_shader: RefCell<Option<gsk::GLShader>>,
+        /// Blurred child with its logical size and the scale factor
+        pub(super) blurred_texture_cache: RefCell<Option<(gdk::Texture, (f32, f32, i32))>>,
 
         #[property(get)]
         pub(super) animation: OnceCell<adw::TimedAnimation>,
 
         #[property(get, set = Self::set_hidden)]
         pub(super) hidden: Cell<bool>,
+
+        #[property(get, set = Self::set_blur_radius, minimum = 0.0, default_value = 128.0)]
+        pub(super) blur_radius: Cell<f64>,
+
+        #[property(get, set = Self::set_blur_quality, minimum = 1, maximum = 64, default_value = 16)]
+        pub(super) blur_quality: Cell<u32>,
+
+        #[property(get, set = Self::set_blur_backend, builder(BlurBackend::default()))]
+        pub(super) blur_backend: Cell<BlurBackend>,
+
+        #[property(get, set, minimum = 0.0, default_value = 12.0)]
+        pub(super) particle_density: Cell<f64>,
+
+        #[property(get, set, minimum = 0.0, default_value = 1.5)]
+        pub(super) particle_size: Cell<f64>,
+
+        #[property(get, set, minimum = 0.0, default_value = 12.0)]
+        pub(super) particle_speed: Cell<f64>,
+
+        #[property(get, set = Self::set_tint_color)]
+        pub(super) tint_color: RefCell<Option<gdk::RGBA>>,
+
+        #[property(get, set = Self::set_particle_color)]
+        pub(super) particle_color: RefCell<Option<gdk::RGBA>>,
+
+        pub(super) style_handlers: RefCell<Vec<glib::SignalHandlerId>>,
+
+        #[property(get, set, builder(RevealTransition::default()))]
+        pub(super) reveal_transition: Cell<RevealTransition>,
+
+        #[property(get, set = Self::set_protected, default_value = true)]
+        pub(super) protected: Cell<bool>,
+        pub(super) protected_child: RefCell<Option<ProtectedChild>>,
+
+        #[property(get, set = Self::set_group)]
+        pub(super) group: RefCell<Option<SpoilerGroup>>,
+        /// Radius shared by the members of the group while they are revealed
+        pub(super) group_reveal_radius: Cell<Option<f32>>,
+
+        #[property(get, set = Self::set_reveal_timeout)]
+        pub(super) reveal_timeout: Cell<u32>,
+        pub(super) countdown: RefCell<Option<Countdown>>,
+        pub(super) countdown_ticker: Ticker,
+
+        pub(super) content_watch: RefCell<Option<ContentWatch>>,
+        pub(super) last_blur_refresh: Cell<i64>,
+        pub(super) blur_refresh_source: RefCell<Option<glib::SourceId>>,
+
+        #[property(get, set = Self::set_live_blur)]
+        pub(super) live_blur: Cell<bool>,
+
+        #[property(get, set, minimum = 1, maximum = 120, default_value = 15)]
+        pub(super) live_blur_fps: Cell<u32>,
+        /// Child redrew since the last live blur refresh
+        pub(super) live_blur_dirty: Cell<bool>,
+    }
+
+    /// Running timer of the `reveal-timeout`
+    pub(super) struct Countdown {
+        start_time: i64,
+        /// Duration in microseconds, `reveal-timeout` can change while the timer runs
+        duration: i64,
+        source: glib::SourceId,
+    }
+
+    /// Child state changed by the protection, restored after reveal
+    pub(super) struct ProtectedChild {
+        widget: gtk::Widget,
+        can_target: bool,
+        can_focus: bool,
+    }
+
+    impl Default for SpoilerOverlay {
+        fn default() -> Self {
+            Self {
+                start_time: Default::default(),
+                ticker: Default::default(),
+                reveal_progress: Cell::new(1.0),
+                reveal_center: Default::default(),
+                reveal_mode: Default::default(),
+                peeking: Default::default(),
+                scratch_strokes: Default::default(),
+                shader: Default::default(),
+                gaussian_shader: Default::default(),
+                blurred_texture_cache: Default::default(),
+                animation: Default::default(),
+                hidden: Default::default(),
+                blur_radius: Cell::new(128.0),
+                blur_quality: Cell::new(16),
+                blur_backend: Default::default(),
+                particle_density: Cell::new(12.0),
+                particle_size: Cell::new(1.5),
+                particle_speed: Cell::new(12.0),
+                tint_color: Default::default(),
+                particle_color: Default::default(),
+                style_handlers: Default::default(),
+                reveal_transition: Default::default(),
+                protected: Cell::new(true),
+                protected_child: Default::default(),
+                group: Default::default(),
+                group_reveal_radius: Default::default(),
+                reveal_timeout: Default::default(),
+                countdown: Default::default(),
+                countdown_ticker: Default::default(),
+                content_watch: Default::default(),
+                last_blur_refresh: Default::default(),
+                blur_refresh_source: Default::default(),
+                live_blur: Default::default(),
+                live_blur_fps: Cell::new(15),
+                live_blur_dirty: Default::default(),
+            }
+        }
     }
 
     #[glib::object_subclass]
@@ -39,6 +254,22 @@ mod imp {
         const NAME: &'static str = "OriSpoilerOverlay";
         type Type = super::SpoilerOverlay;
         type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.set_accessible_role(gtk::AccessibleRole::Button);
+
+            klass.install_action("spoiler.reveal", None, |widget, _, _| {
+                if widget.hidden() {
+                    let x = widget.width() as f32 * 0.5;
+                    let y = widget.height() as f32 * 0.5;
+                    widget.imp().reveal(x, y);
+                }
+            });
+
+            for key in ACTIVATE_KEYS {
+                klass.add_binding_action(key, gdk::ModifierType::empty(), "spoiler.reveal", None);
+            }
+        }
     }
 
     impl ObjectImpl for SpoilerOverlay {
@@ -46,6 +277,26 @@ mod imp {
             Self::derived_properties()
         }
 
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: Lazy<Vec<Signal>> = Lazy::new(|| {
+                vec![
+                    Signal::builder("reveal-requested")
+                        .return_type::<bool>()
+                        .accumulator(|_hint, accumulator, value| {
+                            let cancelled = value.get::<bool>().unwrap_or_default();
+                            *accumulator = value.clone();
+                            // Stop the emission when any handler cancels the reveal
+                            !cancelled
+                        })
+                        .build(),
+                    Signal::builder("revealed").build(),
+                    Signal::builder("hidden-finished").build(),
+                    Signal::builder("expired").build(),
+                ]
+            });
+            SIGNALS.as_ref()
+        }
+
         fn property(&self, id: usize, pspec: &glib::ParamSpec) -> glib::Value {
             self.derived_property(id, pspec)
         }
@@ -57,26 +308,47 @@ mod imp {
         fn constructed(&self) {
             let widget = self.obj();
 
-            self.reveal_progress.set(1.0);
-
             widget.connect_child_notify(|widget| {
                 widget.imp().blurred_texture_cache.take();
+                widget.imp().update_protection();
+                widget.imp().update_content_watch();
             });
 
+            // Blur is rendered at device pixels, so it's outdated on a monitor with another scale
+            widget.connect_scale_factor_notify(|widget| {
+                widget.refresh_blur();
+                widget.queue_draw();
+            });
+
+            // Cached blur contains the tint, so it should be redrawn with the new one
+            let style_manager = adw::StyleManager::default();
+            let refresh_tint = clone!(@weak widget => move |_: &adw::StyleManager| {
+                if widget.tint_color().is_none() {
+                    widget.refresh_blur();
+                    widget.queue_draw();
+                }
+            });
+            self.style_handlers.replace(vec![
+                style_manager.connect_dark_notify(refresh_tint.clone()),
+                style_manager.connect_high_contrast_notify(refresh_tint),
+            ]);
+
             self.parent_constructed();
             self.obj().connect_hidden_notify(|widget| {
                 if widget.hidden() {
-                    widget.imp().start_time.set(widget.time());
-                    widget.add_tick_callback(|widget, _clock| {
+                    widget.imp().start_time.set(widget.imp().ticker.time());
+                    widget.imp().ticker.start(widget, |widget| {
+                        widget.imp().tick_live_blur();
                         widget.queue_draw();
-                        Continue(widget.hidden())
+                        widget.hidden()
                     });
                 }
             });
 
             let target =
                 adw::CallbackAnimationTarget::new(clone!(@weak widget => move |progress| {
-                    widget.imp().reveal_progress.set(progress as f32);
+                    widget.imp().reveal_progress.set(progress);
+                    widget.notify("reveal-progress");
                     widget.queue_draw();
                 }));
 
@@ -84,34 +356,178 @@ mod imp {
                 .widget(&*widget)
                 .value_from(0.0)
                 .value_to(1.0)
-                .duration(1000)
+                .duration(REVEAL_DURATION)
                 .easing(adw::Easing::EaseInOutCubic)
                 .target(&target)
                 .repeat_count(1)
                 .build();
 
+            animation.connect_done(clone!(@weak widget => move |_| {
+                if widget.hidden() {
+                    widget.emit_by_name::<()>("hidden-finished", &[]);
+                } else {
+                    widget.emit_by_name::<()>("revealed", &[]);
+                    widget.imp().start_countdown();
+                }
+            }));
+
             self.animation.set(animation).unwrap();
 
             let controller = gtk::GestureClick::builder().button(1).build();
 
             controller.connect_pressed(clone!(@weak widget => move |_, _button, x, y| {
-                if widget.hidden() {
-                    widget.imp().click_point.set((x as f32, y as f32));
+                if widget.hidden() && widget.reveal_mode() == RevealMode::Click {
+                    widget.imp().reveal(x as f32, y as f32);
+                }
+            }));
+
+            self.obj().add_controller(controller);
+
+            let controller = gtk::GestureDrag::builder().button(1).build();
+
+            controller.connect_drag_begin(clone!(@weak widget => move |gesture, x, y| {
+                let imp = widget.imp();
+
+                if !widget.hidden()
+                    || widget.reveal_mode() != RevealMode::Scratch
+                    || widget.emit_by_name::<bool>("reveal-requested", &[])
+                {
+                    gesture.set_state(gtk::EventSequenceState::Denied);
+                    return;
+                }
+
+                imp.scratch_strokes
+                    .borrow_mut()
+                    .push(vec![graphene::Point::new(x as f32, y as f32)]);
+                widget.queue_draw();
+            }));
+
+            controller.connect_drag_update(clone!(@weak widget => move |gesture, x, y| {
+                if let Some((start_x, start_y)) = gesture.start_point() {
+                    let point = graphene::Point::new((start_x + x) as f32, (start_y + y) as f32);
+
+                    if let Some(stroke) = widget.imp().scratch_strokes.borrow_mut().last_mut() {
+                        stroke.push(point);
+                    }
+
+                    widget.queue_draw();
+                }
+            }));
+
+            controller.connect_drag_end(clone!(@weak widget => move |gesture, x, y| {
+                let imp = widget.imp();
+
+                let bounds =
+                    graphene::Rect::new(0.0, 0.0, widget.width() as f32, widget.height() as f32);
+
+                let coverage = transitions::scratch_coverage(&bounds, &imp.scratch_strokes.borrow());
+
+                if widget.hidden() && coverage >= SCRATCH_REVEAL_COVERAGE {
+                    if let Some((start_x, start_y)) = gesture.start_point() {
+                        let center = graphene::Point::new((start_x + x) as f32, (start_y + y) as f32);
+                        imp.reveal_center.replace(Some(center));
+                        widget.notify("reveal-center");
+                    }
+
                     widget.set_hidden(false);
                 }
             }));
 
             self.obj().add_controller(controller);
+
+            let controller = gtk::GestureLongPress::new();
+
+            controller.connect_pressed(clone!(@weak widget => move |_, x, y| {
+                if widget.hidden() && widget.reveal_mode() == RevealMode::PressAndHold {
+                    widget.imp().peek(x as f32, y as f32);
+                }
+            }));
+
+            controller.connect_end(clone!(@weak widget => move |_, _| {
+                widget.imp().stop_peeking();
+            }));
+
+            self.obj().add_controller(controller);
+
+            // Keyboard peeking, the reveal action is disabled in the press and hold mode
+            let controller = gtk::EventControllerKey::new();
+
+            controller.connect_key_pressed(
+                clone!(@weak widget => @default-return gtk::Inhibit(false), move |_, key, _, _| {
+                    let peekable = widget.reveal_mode() == RevealMode::PressAndHold;
+
+                    if !peekable || !ACTIVATE_KEYS.contains(&key) {
+                        return gtk::Inhibit(false);
+                    }
+
+                    // Repeated presses of the held key are ignored
+                    if widget.hidden() && !widget.imp().peeking.get() {
+                        let x = widget.width() as f32 * 0.5;
+                        let y = widget.height() as f32 * 0.5;
+                        widget.imp().peek(x, y);
+                    }
+
+                    gtk::Inhibit(true)
+                }),
+            );
+
+            controller.connect_key_released(clone!(@weak widget => move |_, key, _, _| {
+                if ACTIVATE_KEYS.contains(&key) {
+                    widget.imp().stop_peeking();
+                }
+            }));
+
+            self.obj().add_controller(controller);
+
+            // Key release isn't delivered after the focus moves away
+            let controller = gtk::EventControllerFocus::new();
+
+            controller.connect_leave(clone!(@weak widget => move |_| {
+                widget.imp().stop_peeking();
+            }));
+
+            self.obj().add_controller(controller);
+
+            self.update_accessibility();
+        }
+
+        fn dispose(&self) {
+            let style_manager = adw::StyleManager::default();
+            for handler in self.style_handlers.take() {
+                style_manager.disconnect(handler);
+            }
+
+            self.stop_countdown();
+
+            if let Some(group) = self.group.take() {
+                group.remove(&self.obj());
+            }
+
+            self.content_watch.take();
+            if let Some(source) = self.blur_refresh_source.take() {
+                source.remove();
+            }
         }
     }
 
     impl WidgetImpl for SpoilerOverlay {
         fn realize(&self) {
             self.parent_realize();
-            self.ensure_shader();
             self.obj().notify("visible");
         }
 
+        fn map(&self) {
+            self.parent_map();
+            self.ticker.update(&*self.obj());
+            self.countdown_ticker.update(&*self.obj());
+        }
+
+        fn unmap(&self) {
+            self.parent_unmap();
+            self.ticker.update(&*self.obj());
+            self.countdown_ticker.update(&*self.obj());
+        }
+
         fn snapshot(&self, snapshot: &gtk::Snapshot) {
             self.parent_snapshot(snapshot);
 
@@ -122,104 +538,591 @@ mod imp {
 
             let bounds = graphene::Rect::new(0.0, 0.0, width, height);
 
-            let (x, y) = if self.hidden.get() {
-                (width * 0.5, height * 0.5)
-            } else {
-                self.click_point.get()
-            };
+            // Nothing is visible when the child is revealed.
+            // Revealed overlays drop the blur after every redraw of the watched pictures,
+            // so drawing the fully masked spoiler would blur the child again each frame
+            if self.reveal_progress.get() < 1.0 {
+                self.snapshot_spoiler(snapshot, &bounds);
+            }
 
-            let center = &graphene::Point::new(x, y);
+            self.snapshot_countdown(snapshot, &bounds);
+        }
+    }
 
-            let max_corner_length = [
-                bounds.top_left(),
-                bounds.top_right(),
-                bounds.bottom_left(),
-                bounds.bottom_right(),
-            ]
-            .into_iter()
-            .map(|v| (v.distance(center)).0)
-            .max_by(|this, other| this.partial_cmp(other).unwrap())
-            .unwrap();
+    impl BinImpl for SpoilerOverlay {}
 
-            let progress = self.reveal_progress.get();
-            let radius = max_corner_length * progress;
+    impl SpoilerOverlay {
+        fn set_hidden(&self, hidden: bool) {
+            let widget = self.obj();
+            let was_hidden = self.hidden.get();
+            let animation = self.animation.get().unwrap();
+            let to = if hidden { 0.0 } else { 1.0 };
 
-            if radius > 0.0 {
-                snapshot.push_mask(gsk::MaskMode::InvertedAlpha);
+            // Playing the animation again would emit the signals and restart the countdown
+            if hidden == was_hidden
+                && animation.state() != adw::AnimationState::Playing
+                && self.reveal_progress.get() == to
+            {
+                return;
+            }
 
-                snapshot.append_radial_gradient(
-                    &bounds,
-                    &graphene::Point::new(x, y),
-                    radius,
-                    radius,
-                    0.0,
-                    1.0,
-                    &[
-                        gsk::ColorStop::new(0.0, gdk::RGBA::BLACK),
-                        gsk::ColorStop::new(progress, gdk::RGBA::BLACK),
-                        gsk::ColorStop::new((progress + 0.5).min(1.0), gdk::RGBA::TRANSPARENT),
-                        gsk::ColorStop::new(1.0, gdk::RGBA::TRANSPARENT),
-                    ],
-                );
-                snapshot.pop();
+            if hidden {
+                self.group_reveal_radius.set(None);
+
+                // Hidden child disappears to the center
+                if self.reveal_center.replace(None).is_some() {
+                    widget.notify("reveal-center");
+                }
+
+                self.scratch_strokes.borrow_mut().clear();
             }
 
-            self.render_blur_texture(snapshot, &bounds);
+            // Animation continues from the current progress,
+            // which could be changed by the app
+            let from = self.reveal_progress.get();
 
-            let time = widget.time() - self.start_time.get();
-            let time = time as f32 / 50000.0;
+            self.hidden.set(hidden);
+            self.update_accessibility();
+            self.update_protection();
 
-            let speed_modifiers = &[
-                (0.468, 0.287),
-                (0.305, 0.1967),
-                (0.316, 0.3239),
-                (-0.0239, 0.7745),
-                (-0.0736, 0.2023),
-                (0.5138, -0.15),
-                (0.5603, -0.8172),
-                (-0.8098, -0.8822),
-            ];
+            if hidden {
+                self.stop_countdown();
+            }
 
-            self.render_particle_layers(snapshot, &bounds, time, speed_modifiers);
+            animation.set_value_from(from);
+            animation.set_value_to(to);
+            animation.set_duration((REVEAL_DURATION as f64 * (to - from).abs()) as u32);
+            animation.play();
 
-            if radius > 0.0 {
-                snapshot.pop();
+            if was_hidden && !hidden && !self.peeking.get() {
+                if let Some(group) = self.group.borrow().clone() {
+                    let center = self.reveal_center.borrow().clone().unwrap_or_else(|| {
+                        graphene::Point::new(
+                            widget.width() as f32 * 0.5,
+                            widget.height() as f32 * 0.5,
+                        )
+                    });
+
+                    group.reveal_from(&widget, &center);
+                }
             }
         }
-    }
 
-    impl BinImpl for SpoilerOverlay {}
+        fn set_group(&self, group: Option<SpoilerGroup>) {
+            let widget = self.obj();
 
-    impl SpoilerOverlay {
-        fn set_hidden(&self, hidden: bool) {
-            let animation = self.animation.get().unwrap();
-            animation.set_reverse(hidden);
-            animation.play();
+            if let Some(old) = self.group.take() {
+                old.remove(&widget);
+            }
 
-            self.hidden.set(hidden);
+            if let Some(group) = &group {
+                group.add(&widget);
+            }
+
+            self.group.replace(group);
+        }
+
+        /// Starts watching for redraws of the new child
+        fn update_content_watch(&self) {
+            let widget = self.obj();
+
+            let watch = widget.child().map(|child| {
+                ContentWatch::new(
+                    &child,
+                    clone!(@weak widget => move || {
+                        widget.imp().update_content_watch();
+                        widget.imp().schedule_blur_refresh();
+                    }),
+                    clone!(@weak widget => move || {
+                        widget.imp().schedule_blur_refresh();
+                    }),
+                )
+            });
+
+            self.content_watch.replace(watch);
+        }
+
+        /// Refreshes the blur after the child redraws
+        ///
+        /// The blur is refreshed at most once per [BLUR_REFRESH_INTERVAL]
+        fn schedule_blur_refresh(&self) {
+            let widget = self.obj();
+
+            // The cache will be filled again when the spoiler is visible
+            if !self.hidden.get() {
+                widget.refresh_blur();
+                return;
+            }
+
+            // Live blur is refreshed by the ticker at `live-blur-fps`
+            if self.live_blur.get() {
+                self.live_blur_dirty.set(true);
+                return;
+            }
+
+            if self.blur_refresh_source.borrow().is_some() {
+                return;
+            }
+
+            let elapsed = glib::monotonic_time() - self.last_blur_refresh.get();
+
+            if elapsed >= BLUR_REFRESH_INTERVAL {
+                self.refresh_blur_now();
+                return;
+            }
+
+            let source = glib::timeout_add_local_once(
+                std::time::Duration::from_micros((BLUR_REFRESH_INTERVAL - elapsed) as u64),
+                clone!(@weak widget => move || {
+                    widget.imp().blur_refresh_source.take();
+                    widget.imp().refresh_blur_now();
+                }),
+            );
+
+            self.blur_refresh_source.replace(Some(source));
+        }
+
+        fn refresh_blur_now(&self) {
+            self.last_blur_refresh.set(glib::monotonic_time());
+            self.obj().refresh_blur();
+            self.obj().queue_draw();
+        }
+
+        fn set_live_blur(&self, live_blur: bool) {
+            self.live_blur.set(live_blur);
+            self.obj().refresh_blur();
+            self.obj().queue_draw();
+        }
+
+        /// Drops the cached blur when the child redrew,
+        /// but not more often than once per `live-blur-fps` frame
+        fn tick_live_blur(&self) {
+            if !self.live_blur.get() || !self.live_blur_dirty.get() {
+                return;
+            }
+
+            let now = glib::monotonic_time();
+            let frame_duration = 1_000_000 / self.live_blur_fps.get().max(1) as i64;
+
+            if now - self.last_blur_refresh.get() >= frame_duration {
+                self.live_blur_dirty.set(false);
+                self.last_blur_refresh.set(now);
+                self.obj().refresh_blur();
+            }
         }
 
-        fn ensure_shader(&self) {
+        fn set_reveal_timeout(&self, timeout: u32) {
+            self.reveal_timeout.set(timeout);
+
+            if timeout == 0 {
+                self.stop_countdown();
+            }
+        }
+
+        /// Starts the timer which hides the child after `reveal-timeout` seconds
+        fn start_countdown(&self) {
+            self.stop_countdown();
+
+            let timeout = self.reveal_timeout.get();
+
+            if timeout == 0 {
+                return;
+            }
+
             let widget = self.obj();
-            if self.shader.borrow().is_none() {
+
+            // Seconds timeouts are coalesced, so the ring would run out before the hide
+            let source = glib::timeout_add_local_once(
+                std::time::Duration::from_secs(timeout as u64),
+                clone!(@weak widget => move || {
+                    // The source is already destroyed
+                    widget.imp().countdown.take();
+
+                    widget.set_hidden(true);
+                    widget.emit_by_name::<()>("expired", &[]);
+                }),
+            );
+
+            self.countdown.replace(Some(Countdown {
+                start_time: glib::monotonic_time(),
+                duration: timeout as i64 * 1_000_000,
+                source,
+            }));
+
+            self.countdown_ticker.start(&*widget, |widget| {
+                widget.queue_draw();
+                widget.imp().countdown.borrow().is_some()
+            });
+        }
+
+        fn stop_countdown(&self) {
+            if let Some(countdown) = self.countdown.take() {
+                countdown.source.remove();
+                self.obj().queue_draw();
+            }
+        }
+
+        fn set_reveal_progress(&self, progress: f64) {
+            // The app drives the progress
+            if let Some(animation) = self.animation.get() {
+                if animation.state() == adw::AnimationState::Playing {
+                    animation.pause();
+                }
+            }
+
+            self.reveal_progress.set(progress);
+            self.obj().queue_draw();
+        }
+
+        fn set_reveal_mode(&self, mode: RevealMode) {
+            self.reveal_mode.set(mode);
+            self.update_accessibility();
+        }
+
+        fn set_reveal_center(&self, center: Option<graphene::Point>) {
+            self.reveal_center.replace(center);
+            self.obj().queue_draw();
+        }
+
+        fn set_protected(&self, protected: bool) {
+            self.protected.set(protected);
+            self.update_protection();
+        }
+
+        /// Stops input, focus and accessibility from reaching the hidden child
+        fn update_protection(&self) {
+            let child = self.obj().child();
+            let protect = self.hidden.get() && self.protected.get();
+
+            if protect
+                && self.protected_child.borrow().as_ref().map(|p| &p.widget) == child.as_ref()
+            {
+                return;
+            }
+
+            if let Some(previous) = self.protected_child.take() {
+                previous.widget.set_can_target(previous.can_target);
+                previous.widget.set_can_focus(previous.can_focus);
+                previous.widget.reset_state(gtk::AccessibleState::Hidden);
+            }
+
+            if let Some(child) = child.filter(|_| protect) {
+                self.protected_child.replace(Some(ProtectedChild {
+                    can_target: child.can_target(),
+                    can_focus: child.can_focus(),
+                    widget: child.clone(),
+                }));
+
+                // Without input the child can't start drags or show context menus
+                child.set_can_target(false);
+                child.set_can_focus(false);
+                child.update_state(&[gtk::accessible::State::Hidden(true)]);
+            }
+        }
+
+        /// Reveals the child with the animation starting at the point
+        ///
+        /// Does nothing if a `reveal-requested` handler cancels the reveal
+        pub(super) fn reveal(&self, x: f32, y: f32) {
+            let widget = self.obj();
+
+            self.reveal_center.replace(Some(graphene::Point::new(x, y)));
+            widget.notify("reveal-center");
+
+            if !widget.emit_by_name::<bool>("reveal-requested", &[]) {
+                widget.set_hidden(false);
+            }
+        }
+
+        /// Reveals the child only until [Self::stop_peeking] is called
+        ///
+        /// Peeking is set before the reveal, so the group members stay hidden
+        fn peek(&self, x: f32, y: f32) {
+            self.peeking.set(true);
+            self.reveal(x, y);
+
+            // The reveal was cancelled
+            if self.hidden.get() {
+                self.peeking.set(false);
+            }
+        }
+
+        fn stop_peeking(&self) {
+            if self.peeking.replace(false) {
+                // Hide back to the point where the child was revealed
+                let widget = self.obj();
+                let center = self.reveal_center.borrow().clone();
+                widget.set_hidden(true);
+                self.reveal_center.replace(center);
+                widget.notify("reveal-center");
+            }
+        }
+
+        /// Hidden overlay behaves like a button which reveals the content
+        ///
+        /// In the press and hold mode the keys only reveal the content while they're held,
+        /// so the overlay stays focused while peeking and the reveal action is disabled
+        fn update_accessibility(&self) {
+            let widget = self.obj();
+            let mode = self.reveal_mode.get();
+            let revealable =
+                (self.hidden.get() || self.peeking.get()) && mode != RevealMode::Programmatic;
+
+            widget.set_focusable(revealable);
+            widget.action_set_enabled(
+                "spoiler.reveal",
+                revealable && mode != RevealMode::PressAndHold,
+            );
+
+            if revealable {
+                widget
+                    .update_property(&[gtk::accessible::Property::Label("Reveal hidden content")]);
+            } else {
+                widget.reset_property(gtk::AccessibleProperty::Label);
+            }
+        }
+
+        fn set_blur_radius(&self, radius: f64) {
+            if self.blur_radius.replace(radius) != radius {
+                self.obj().refresh_blur();
+                self.obj().queue_draw();
+            }
+        }
+
+        fn set_blur_quality(&self, quality: u32) {
+            if self.blur_quality.replace(quality) != quality {
+                self.obj().refresh_blur();
+                self.obj().queue_draw();
+            }
+        }
+
+        fn set_tint_color(&self, color: Option<gdk::RGBA>) {
+            if self.tint_color.replace(color.clone()) != color {
+                self.obj().refresh_blur();
+                self.obj().queue_draw();
+            }
+        }
+
+        fn set_particle_color(&self, color: Option<gdk::RGBA>) {
+            self.particle_color.replace(color);
+            self.obj().queue_draw();
+        }
+
+        /// Color drawn under the blurred child
+        fn tint(&self) -> gdk::RGBA {
+            self.tint_color
+                .borrow()
+                .clone()
+                .unwrap_or_else(default_tint_color)
+        }
+
+        fn particles(&self) -> Particles {
+            Particles {
+                density: self.particle_density.get() as f32,
+                size: self.particle_size.get() as f32,
+                speed: self.particle_speed.get() as f32,
+                scale: self.obj().scale_factor() as f32,
+                ..Default::default()
+            }
+        }
+
+        fn set_blur_backend(&self, backend: BlurBackend) {
+            if self.blur_backend.replace(backend) != backend {
+                self.obj().refresh_blur();
+                self.obj().queue_draw();
+            }
+        }
+
+        fn ensure_shader(&self, shader: &RefCell<Option<gsk::GLShader>>, source: &'static [u8]) {
+            let widget = self.obj();
+            if shader.borrow().is_none() && widget.native().is_some() {
                 let renderer = widget.native().unwrap().renderer();
 
-                let shader = gsk::GLShader::from_bytes(&BLUR_SHADER.into());
-                match shader.compile(&renderer) {
+                let compiled = gsk::GLShader::from_bytes(&source.into());
+                match compiled.compile(&renderer) {
                     Err(e) => {
                         if !e.matches(gio::IOErrorEnum::NotSupported) {
                             log::error!("can't compile the blur shader {e}");
                         }
                     }
                     Ok(_) => {
-                        self.shader.replace(Some(shader));
+                        shader.replace(Some(compiled));
+                    }
+                }
+            };
+        }
+
+        fn snapshot_spoiler(&self, snapshot: &gtk::Snapshot, bounds: &graphene::Rect) {
+            let widget = self.obj();
+
+            let width = bounds.width();
+            let height = bounds.height();
+
+            let center = self
+                .reveal_center
+                .borrow()
+                .clone()
+                .unwrap_or_else(|| graphene::Point::new(width * 0.5, height * 0.5));
+
+            let progress = self.reveal_progress.get() as f32;
+            let transition = self.reveal_transition.get();
+
+            let scratched = !self.scratch_strokes.borrow().is_empty();
+
+            if scratched {
+                snapshot.push_mask(gsk::MaskMode::InvertedAlpha);
+                transitions::append_scratch_mask(snapshot, bounds, &self.scratch_strokes.borrow());
+                snapshot.pop();
+            }
+
+            let masked = match transition {
+                RevealTransition::Radial => {
+                    let radius = match self.group_reveal_radius.get() {
+                        Some(radius) => radius * progress,
+                        None => reveal_radius(bounds, &center, progress),
+                    };
+
+                    if radius > 0.0 {
+                        snapshot.push_mask(gsk::MaskMode::InvertedAlpha);
+                        append_reveal_gradient(snapshot, bounds, &center, radius, progress);
+                        snapshot.pop();
                     }
+
+                    radius > 0.0
+                }
+                RevealTransition::Crossfade => {
+                    snapshot.push_opacity(1.0 - progress as f64);
+                    true
+                }
+                RevealTransition::Dissolve => {
+                    snapshot.push_mask(gsk::MaskMode::InvertedAlpha);
+                    transitions::append_dissolve_mask(snapshot, bounds, progress);
+                    snapshot.pop();
+                    true
+                }
+                RevealTransition::Wipe => {
+                    snapshot.push_mask(gsk::MaskMode::InvertedAlpha);
+                    transitions::append_wipe_mask(snapshot, bounds, &center, progress);
+                    snapshot.pop();
+                    true
                 }
             };
+
+            self.render_blur_texture(snapshot, bounds);
+
+            let mut particles = self.particles();
+
+            if transition == RevealTransition::Dissolve {
+                // Particles fly away from the dissolving spoiler
+                snapshot.pop();
+                snapshot.push_opacity(1.0 - progress as f64);
+
+                particles.scatter_center = (center.x(), center.y());
+                particles.scatter = progress;
+            }
+
+            let time = self.ticker.time() - self.start_time.get();
+            let particle_color = self.particle_color().unwrap_or_else(|| widget.color());
+            particles.render(snapshot, bounds, particles_time(time), &particle_color);
+
+            if masked {
+                snapshot.pop();
+            }
+
+            if scratched {
+                snapshot.pop();
+            }
+        }
+
+        fn snapshot_countdown(&self, snapshot: &gtk::Snapshot, bounds: &graphene::Rect) {
+            let widget = self.obj();
+            let width = bounds.width();
+
+            if let Some(countdown) = &*self.countdown.borrow() {
+                let duration = countdown.duration as f64;
+                // The timeout runs even when the widget isn't visible,
+                // so the ring follows the real time instead of the paused one
+                let elapsed = (glib::monotonic_time() - countdown.start_time) as f64;
+                let remaining = (1.0 - elapsed / duration).clamp(0.0, 1.0);
+
+                let ring_bounds = graphene::Rect::new(
+                    width - COUNTDOWN_SIZE - COUNTDOWN_MARGIN,
+                    COUNTDOWN_MARGIN,
+                    COUNTDOWN_SIZE,
+                    COUNTDOWN_SIZE,
+                );
+
+                let mut background = self.tint();
+                background.set_alpha(0.6);
+
+                snapshot.push_rounded_clip(&gsk::RoundedRect::from_rect(
+                    ring_bounds.clone(),
+                    COUNTDOWN_SIZE * 0.5,
+                ));
+                snapshot.append_color(&background, &ring_bounds);
+                snapshot.pop();
+
+                let color = self.particle_color().unwrap_or_else(|| widget.color());
+                let pi = std::f64::consts::PI;
+
+                append_arc(
+                    snapshot,
+                    &ring_bounds,
+                    &color,
+                    -0.5 * pi,
+                    remaining * 2.0 * pi,
+                );
+            }
+        }
+
+        /// Child picture with its paintable
+        ///
+        /// Blurred textures of such children are shared through the [BlurCache].
+        /// Other children can draw anything around the paintable,
+        /// so their blur is never shared
+        fn shared_content(&self) -> Option<(gtk::Picture, gdk::Paintable)> {
+            if self.live_blur.get() {
+                return None;
+            }
+
+            let picture = self.obj().child()?.downcast::<gtk::Picture>().ok()?;
+            let paintable = picture.paintable()?;
+
+            Some((picture, paintable))
+        }
+
+        fn blur_key(
+            &self,
+            picture: &gtk::Picture,
+            content: &gdk::Paintable,
+            bounds: &graphene::Rect,
+        ) -> BlurKey {
+            let mut hasher = DefaultHasher::new();
+            self.blur_radius.get().to_bits().hash(&mut hasher);
+            self.blur_quality.get().hash(&mut hasher);
+            (self.blur_backend.get() as i32).hash(&mut hasher);
+            self.tint().to_string().hash(&mut hasher);
+
+            // The picture places the paintable by its content fit,
+            // style classes can add backgrounds and rounded corners
+            (picture.content_fit() as i32).hash(&mut hasher);
+            for class in picture.css_classes() {
+                class.hash(&mut hasher);
+            }
+
+            BlurKey {
+                content: content.as_ptr() as usize,
+                size: (
+                    bounds.width().to_bits(),
+                    bounds.height().to_bits(),
+                    self.obj().scale_factor(),
+                ),
+                settings: hasher.finish(),
+            }
         }
 
         fn render_blur_texture(&self, snapshot: &gtk::Snapshot, bounds: &graphene::Rect) {
-            let current_size = (bounds.width(), bounds.height());
+            let current_size = (bounds.width(), bounds.height(), self.obj().scale_factor());
 
             match self.blurred_texture_cache.borrow().as_ref() {
                 Some((texture, size)) if *size == current_size => {
@@ -229,62 +1132,173 @@ mod imp {
                 _ => {}
             };
 
-            if let Some(shader) = &*self.shader.borrow() {
-                let texture = {
-                    let snapshot = gtk::Snapshot::new();
+            let content = self.shared_content();
+            let key = content
+                .as_ref()
+                .map(|(picture, content)| self.blur_key(picture, content, bounds));
 
-                    let args = gsk::ShaderArgsBuilder::new(shader, None);
-                    snapshot.push_gl_shader(shader, bounds, args.to_args());
-                    snapshot.append_color(&gdk::RGBA::new(0.3, 0.3, 0.3, 1.0), bounds);
-                    self.parent_snapshot(&snapshot);
-                    snapshot.gl_shader_pop_texture();
-                    snapshot.pop();
+            if let Some(texture) = key.as_ref().and_then(BlurCache::lookup) {
+                snapshot.append_texture(&texture, bounds);
+                self.blurred_texture_cache
+                    .replace(Some((texture, current_size)));
+                return;
+            }
 
-                    let renderer = self.obj().native().unwrap().renderer();
+            let texture = match self.blur_backend.get() {
+                BlurBackend::Gaussian => {
+                    self.ensure_shader(&self.gaussian_shader, GAUSSIAN_SHADER);
 
-                    let Some(node) = snapshot.to_node() else {
-                        return; // nothing to render
-                    };
+                    // Blur node works on the renderers without shaders
+                    match &*self.gaussian_shader.borrow() {
+                        Some(shader) => self.render_gaussian_blur(shader, bounds),
+                        None => self.render_node_blur(bounds),
+                    }
+                }
+                BlurBackend::GlShader => {
+                    self.ensure_shader(&self.shader, BLUR_SHADER);
+
+                    match &*self.shader.borrow() {
+                        Some(shader) => self.render_shader_blur(shader, bounds),
+                        None => self.render_cpu_blur(bounds),
+                    }
+                }
+                BlurBackend::BlurNode => self.render_node_blur(bounds),
+                BlurBackend::Cpu => self.render_cpu_blur(bounds),
+            };
 
-                    renderer.render_texture(node, Some(bounds))
-                };
+            let Some(texture) = texture else {
+                return; // nothing to render
+            };
 
-                snapshot.append_texture(&texture, bounds);
+            snapshot.append_texture(&texture, bounds);
 
-                self.blurred_texture_cache
-                    .replace(Some((texture, current_size)));
-            } else {
-                snapshot.append_color(&gdk::RGBA::new(0.3, 0.3, 0.3, 1.0), bounds);
+            if let (Some(key), Some((_, content))) = (key, content) {
+                BlurCache::insert(key, &content, &texture);
             }
+
+            self.blurred_texture_cache
+                .replace(Some((texture, current_size)));
+        }
+
+        fn render_shader_blur(
+            &self,
+            shader: &gsk::GLShader,
+            bounds: &graphene::Rect,
+        ) -> Option<gdk::Texture> {
+            // Live blur is redrawn every few frames, so it's rendered at lower resolution.
+            // The shader radius is relative to the texture size, so it stays the same
+            let scale_factor = self.obj().scale_factor() as f32;
+            let scale = if self.live_blur.get() {
+                blur_downscale(self.blur_radius.get() as f32, scale_factor)
+            } else {
+                scale_factor
+            };
+
+            let snapshot = gtk::Snapshot::new();
+            snapshot.scale(scale, scale);
+
+            let args = gsk::ShaderArgsBuilder::new(shader, None);
+            args.set_float(
+                shader.find_uniform_by_name("u_radius"),
+                self.blur_radius.get() as f32,
+            );
+            args.set_float(
+                shader.find_uniform_by_name("u_quality"),
+                self.blur_quality.get() as f32,
+            );
+            snapshot.push_gl_shader(shader, bounds, args.to_args());
+            snapshot.append_color(&self.tint(), bounds);
+            self.parent_snapshot(&snapshot);
+            snapshot.gl_shader_pop_texture();
+            snapshot.pop();
+
+            self.render_scaled_texture(snapshot, bounds, scale)
         }
 
-        fn render_particle_layers(
+        /// Separable blur, each pass takes a few samples of the downscaled child
+        fn render_gaussian_blur(
             &self,
-            snapshot: &gtk::Snapshot,
+            shader: &gsk::GLShader,
             bounds: &graphene::Rect,
-            time: f32,
-            speed_modifiers: &[(f32, f32)],
-        ) {
-            let texture = &*PARTICLE_TEXTURE;
+        ) -> Option<gdk::Texture> {
+            let radius = self.blur_radius.get() as f32;
+            let scale = blur_downscale(radius, self.obj().scale_factor() as f32);
 
-            let texture_bounds = {
-                // Texture have 2x size, so we divide it to scale correctly;
-                let width = texture.width() as f32 / 2.0;
-                let height = texture.height() as f32 / 2.0;
+            // One sample per pixel of the downscaled texture is enough
+            let samples = (radius * scale).ceil().clamp(1.0, MAX_GAUSSIAN_SAMPLES);
 
-                graphene::Rect::new(0.0, 0.0, width, height)
+            let pass_args = |direction: [f32; 2]| {
+                let args = gsk::ShaderArgsBuilder::new(shader, None);
+                args.set_float(shader.find_uniform_by_name("u_radius"), radius);
+                args.set_vec2(
+                    shader.find_uniform_by_name("u_direction"),
+                    &graphene::Vec2::new(direction[0], direction[1]),
+                );
+                args.set_float(shader.find_uniform_by_name("u_samples"), samples);
+                args.to_args()
             };
 
-            for (x, y) in speed_modifiers {
-                let x = x * time;
-                let y = y * time;
+            let snapshot = gtk::Snapshot::new();
+            snapshot.scale(scale, scale);
 
-                snapshot.push_repeat(bounds, None);
-                snapshot.translate(&graphene::Point::new(x, y));
-                snapshot.append_texture(texture, &texture_bounds);
-                snapshot.translate(&graphene::Point::new(-x, -y));
-                snapshot.pop();
-            }
+            snapshot.push_gl_shader(shader, bounds, pass_args([0.0, 1.0]));
+            snapshot.push_gl_shader(shader, bounds, pass_args([1.0, 0.0]));
+            snapshot.append_color(&self.tint(), bounds);
+            self.parent_snapshot(&snapshot);
+            snapshot.gl_shader_pop_texture();
+            snapshot.pop();
+            snapshot.gl_shader_pop_texture();
+            snapshot.pop();
+
+            self.render_scaled_texture(snapshot, bounds, scale)
+        }
+
+        /// Renderer-agnostic blur
+        fn render_node_blur(&self, bounds: &graphene::Rect) -> Option<gdk::Texture> {
+            let radius = self.blur_radius.get() as f32;
+            let scale = blur_downscale(radius, self.obj().scale_factor() as f32);
+
+            let snapshot = gtk::Snapshot::new();
+            snapshot.scale(scale, scale);
+            append_blur(&snapshot, bounds, radius, &self.tint(), |snapshot| {
+                self.parent_snapshot(snapshot)
+            });
+
+            self.render_scaled_texture(snapshot, bounds, scale)
+        }
+
+        /// Fallback for renderers which don't support [gsk::GLShader]
+        fn render_cpu_blur(&self, bounds: &graphene::Rect) -> Option<gdk::Texture> {
+            let radius = self.blur_radius.get() as f32;
+            let scale = blur_downscale(radius, self.obj().scale_factor() as f32);
+
+            let snapshot = gtk::Snapshot::new();
+            snapshot.scale(scale, scale);
+            snapshot.append_color(&self.tint(), bounds);
+            self.parent_snapshot(&snapshot);
+
+            let texture = self.render_scaled_texture(snapshot, bounds, scale)?;
+
+            Some(cpu_blur::blur(&texture, radius * scale))
+        }
+
+        fn render_scaled_texture(
+            &self,
+            snapshot: gtk::Snapshot,
+            bounds: &graphene::Rect,
+            scale: f32,
+        ) -> Option<gdk::Texture> {
+            let renderer = self.obj().native()?.renderer();
+            let node = snapshot.to_node()?;
+
+            let viewport = graphene::Rect::new(
+                0.0,
+                0.0,
+                (bounds.width() * scale).ceil(),
+                (bounds.height() * scale).ceil(),
+            );
+
+            Some(renderer.render_texture(node, Some(&viewport)))
         }
     }
 }
@@ -306,6 +1320,91 @@ glib::wrapper! {
     /// Controls how overlay appears and disappears,
     /// It was made as a property to be visible in the Inspector
     ///
+    /// * Reveal progress: [f64] between 0 and 1.
+    /// Progress of the reveal animation, `0` when the child is hidden.
+    /// Setting it pauses the built-in animation, so the reveal can be driven by a gesture
+    /// or an external animation. Changing `hidden` animates from the current progress.
+    ///
+    /// * Reveal center: [Option]<[graphene::Point]>.
+    /// Point where the reveal animation starts, the center of the overlay is used when it's [None].
+    /// It's set to the click point by the user and reset when the child is hidden.
+    ///
+    /// * Reveal mode: [RevealMode].
+    /// User interaction which reveals the child, [RevealMode::Click] by default.
+    /// With [RevealMode::PressAndHold] the child is hidden back when the press is released.
+    ///
+    /// * Reveal timeout: [u32].
+    /// Seconds after which the revealed child is hidden back, `0` disables the timer.
+    /// The countdown ring is displayed in the corner while the timer runs.
+    ///
+    /// * Group: [Option]<[SpoilerGroup]>.
+    /// Overlays of the same group are revealed together.
+    ///
+    /// * Reveal transition: [RevealTransition].
+    /// Animation used to reveal and hide the child, [RevealTransition::Radial] by default.
+    ///
+    /// * Protected: [bool].
+    /// While the overlay is hidden the child doesn't receive input and focus,
+    /// and it's hidden from the accessibility tree. Enabled by default.
+    ///
+    /// * Blur radius: [f64].
+    /// Radius of the blur in pixels, `128` by default.
+    ///
+    /// * Blur quality: [u32] between 1 and 64.
+    /// Amount of samples taken along every blur direction, `16` by default.
+    /// Small spoilers look fine with lower values and render faster.
+    /// Only used by [BlurBackend::GlShader], it has no effect with the default backend
+    ///
+    /// * Blur backend: [BlurBackend].
+    /// Method used to blur the child, [BlurBackend::Gaussian] by default.
+    /// [BlurBackend::BlurNode] works on every renderer
+    ///
+    /// * Live blur: [bool].
+    /// Re-blurs the child while it's hidden, so videos and animations keep moving under the spoiler.
+    /// The blur is refreshed when a [gtk::Picture] inside the child redraws
+    /// and it's rendered at lower resolution to stay cheap.
+    ///
+    /// * Live blur FPS: [u32] between 1 and 120.
+    /// Most times per second the live blur is refreshed, `15` by default.
+    ///
+    /// * Particle density: [f64].
+    /// Amount of particles per 100x100 pixels, `12` by default.
+    ///
+    /// * Particle size: [f64].
+    /// Average particle size in pixels, `1.5` by default.
+    ///
+    /// * Particle speed: [f64].
+    /// Average particle speed in pixels per second, `12` by default.
+    ///
+    /// * Tint color: [Option]<[gdk::RGBA]>.
+    /// Color mixed with the blurred child,
+    /// by default it's gray which follows the dark and high contrast styles.
+    ///
+    /// * Particle color: [Option]<[gdk::RGBA]>.
+    /// Color of the particles, the CSS `color` is used by default.
+    ///
+    /// # Signals
+    ///
+    /// * `reveal-requested`.
+    /// Emitted when the user tries to reveal the child,
+    /// a handler can return [true] to cancel the reveal.
+    /// It isn't emitted when the `hidden` property is changed by the app.
+    ///
+    /// * `revealed`.
+    /// Emitted when the reveal animation is finished.
+    ///
+    /// * `hidden-finished`.
+    /// Emitted when the hide animation is finished.
+    ///
+    /// * `expired`.
+    /// Emitted when the child is hidden back after the `reveal-timeout`.
+    ///
+    /// # Actions
+    ///
+    /// * `spoiler.reveal`.
+    /// Reveals the child with the animation starting at the center,
+    /// it's activated by <kbd>Enter</kbd> and <kbd>Space</kbd> when the overlay is focused.
+    ///
     /// # Bluerpint example
     /// ```blp
     /// $OriSpoilerOverlay {
@@ -320,21 +1419,209 @@ glib::wrapper! {
         @extends adw::Bin, gtk::Widget, @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
 }
 
-impl SpoilerOverlay {}
+impl SpoilerOverlay {
+    /// Reveals the child with the animation starting from the point
+    ///
+    /// Unlike a click it doesn't emit `reveal-requested`
+    pub fn reveal_at(&self, x: f64, y: f64) {
+        let imp = self.imp();
+        imp.reveal_center
+            .replace(Some(graphene::Point::new(x as f32, y as f32)));
+        self.notify("reveal-center");
+
+        self.set_hidden(false);
+    }
+
+    /// Hides the child with the animation converging to the point
+    pub fn hide_from(&self, x: f64, y: f64) {
+        self.set_hidden(true);
+
+        let imp = self.imp();
+        imp.reveal_center
+            .replace(Some(graphene::Point::new(x as f32, y as f32)));
+        self.notify("reveal-center");
+    }
+
+    /// Same as [SpoilerOverlay::reveal_at], but resolves when the animation stops
+    ///
+    /// Resolves to `true` when the child is fully revealed
+    /// and to `false` when the animation was interrupted,
+    /// e.g. by [SpoilerOverlay::hide_from] or a change of `reveal-progress`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # let overlay: origami::SpoilerOverlay = unimplemented!();
+    /// # let video: gtk::Video = unimplemented!();
+    /// gtk::glib::MainContext::default().spawn_local(async move {
+    ///     if overlay.reveal_at_future(10.0, 20.0).await {
+    ///         video.media_stream().unwrap().play();
+    ///     }
+    /// });
+    /// ```
+    pub fn reveal_at_future(&self, x: f64, y: f64) -> impl Future<Output = bool> {
+        let future = AnimationFuture::new(&self.animation(), 1.0);
+        self.reveal_at(x, y);
+        future
+    }
+
+    /// Same as [SpoilerOverlay::hide_from], but resolves when the animation stops
+    ///
+    /// Resolves to `true` when the child is fully hidden
+    /// and to `false` when the animation was interrupted
+    pub fn hide_from_future(&self, x: f64, y: f64) -> impl Future<Output = bool> {
+        let future = AnimationFuture::new(&self.animation(), 0.0);
+        self.hide_from(x, y);
+        future
+    }
+
+    /// Sets the `hidden` property and resolves when the animation stops
+    ///
+    /// Resolves to `true` when the child reached the requested state
+    /// and to `false` when the animation was interrupted
+    pub fn set_hidden_future(&self, hidden: bool) -> impl Future<Output = bool> {
+        let target = if hidden { 0.0 } else { 1.0 };
+        let future = AnimationFuture::new(&self.animation(), target);
+        self.set_hidden(hidden);
+        future
+    }
+}
 
 impl SpoilerOverlay {
     /// Force remove current blur texture from the cache
     ///
-    /// [SpoilerOverlay] automatically refreshes blur on size change or when the child it replaced
-    /// but it doesn't handle when the child redraws something
+    /// [SpoilerOverlay] automatically refreshes blur on size change, when the child is replaced
+    /// or when a [gtk::Picture] inside of the child redraws,
+    /// other changes of the child should be handled manually
     pub fn refresh_blur(&self) {
         self.imp().blurred_texture_cache.take();
+
+        if let Some((_, content)) = self.imp().shared_content() {
+            BlurCache::forget(&content);
+        }
+    }
+
+    /// Connects to the `reveal-requested` signal
+    ///
+    /// Return [true] from the callback to cancel the reveal,
+    /// it can be done later by setting `hidden` to [false]
+    pub fn connect_reveal_requested<F: Fn(&Self) -> bool + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "reveal-requested",
+            false,
+            glib::closure_local!(move |obj: Self| -> bool { f(&obj) }),
+        )
+    }
+
+    /// Connects to the `revealed` signal
+    pub fn connect_revealed<F: Fn(&Self) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "revealed",
+            false,
+            glib::closure_local!(move |obj: Self| f(&obj)),
+        )
+    }
+
+    /// Connects to the `hidden-finished` signal
+    pub fn connect_hidden_finished<F: Fn(&Self) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "hidden-finished",
+            false,
+            glib::closure_local!(move |obj: Self| f(&obj)),
+        )
+    }
+
+    /// Connects to the `expired` signal
+    pub fn connect_expired<F: Fn(&Self) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "expired",
+            false,
+            glib::closure_local!(move |obj: Self| f(&obj)),
+        )
     }
+}
+
+/// Scale the content should be rendered at before blurring with the `radius`
+///
+/// The result is relative to the logical pixels and never exceeds the `scale_factor`
+pub(crate) fn blur_downscale(radius: f32, scale_factor: f32) -> f32 {
+    (DOWNSCALED_BLUR_RADIUS / radius).min(scale_factor)
+}
 
-    fn time(&self) -> i64 {
-        self.frame_clock()
-            .and_then(|clk| clk.current_timings())
-            .map(|t| t.frame_time())
-            .unwrap_or_default()
+/// Tint that follows the current color scheme
+pub(crate) fn default_tint_color() -> gdk::RGBA {
+    let style_manager = adw::StyleManager::default();
+
+    match (style_manager.is_dark(), style_manager.is_high_contrast()) {
+        (true, false) => gdk::RGBA::new(0.3, 0.3, 0.3, 1.0),
+        (true, true) => gdk::RGBA::new(0.1, 0.1, 0.1, 1.0),
+        (false, false) => gdk::RGBA::new(0.8, 0.8, 0.8, 1.0),
+        (false, true) => gdk::RGBA::new(0.95, 0.95, 0.95, 1.0),
     }
 }
+
+/// Draws the blurred `content` over the `tint` using the blur node
+pub(crate) fn append_blur(
+    snapshot: &gtk::Snapshot,
+    bounds: &graphene::Rect,
+    radius: f32,
+    tint: &gdk::RGBA,
+    content: impl FnOnce(&gtk::Snapshot),
+) {
+    snapshot.push_clip(bounds);
+    // Blur node radius is twice bigger than the shader one
+    snapshot.push_blur((radius * 0.5) as f64);
+    snapshot.append_color(tint, &bounds.inset_r(-radius, -radius));
+    content(snapshot);
+    snapshot.pop();
+    snapshot.pop();
+}
+
+/// Converts the time since the start of the animation to seconds
+pub(crate) fn particles_time(time: i64) -> f32 {
+    time as f32 / 1_000_000.0
+}
+
+/// Radius of the revealed circle needed to cover the whole `area` when the `progress` is `1.0`
+pub(crate) fn reveal_radius(area: &graphene::Rect, center: &graphene::Point, progress: f32) -> f32 {
+    let max_corner_length = [
+        area.top_left(),
+        area.top_right(),
+        area.bottom_left(),
+        area.bottom_right(),
+    ]
+    .into_iter()
+    .map(|v| (v.distance(center)).0)
+    .max_by(|this, other| this.partial_cmp(other).unwrap())
+    .unwrap();
+
+    max_corner_length * progress
+}
+
+/// Appends the gradient which is opaque inside of the revealed circle
+///
+/// Expected to be used as a mask source
+pub(crate) fn append_reveal_gradient(
+    snapshot: &gtk::Snapshot,
+    bounds: &graphene::Rect,
+    center: &graphene::Point,
+    radius: f32,
+    progress: f32,
+) {
+    snapshot.append_radial_gradient(
+        bounds,
+        center,
+        radius,
+        radius,
+        0.0,
+        1.0,
+        &[
+            gsk::ColorStop::new(0.0, gdk::RGBA::BLACK),
+            gsk::ColorStop::new(progress, gdk::RGBA::BLACK),
+            gsk::ColorStop::new((progress + 0.5).min(1.0), gdk::RGBA::TRANSPARENT),
+            gsk::ColorStop::new(1.0, gdk::RGBA::TRANSPARENT),
+        ],
+    );
+}
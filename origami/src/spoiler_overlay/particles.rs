@@ -0,0 +1,158 @@
+//! Procedural spoiler particles
+//!
+//! Particles don't have any state, position of every particle is computed
+//! from its seed and the time, so the same particles can be drawn by any widget.
+//!
+//! The plane is divided into cells, every cell has its own set of particles,
+//! so particles stay in place when the spoiler is resized.
+
+use gtk::{gdk, graphene};
+
+/// Size of the cell, density is measured in particles per cell
+const CELL_SIZE: f32 = 100.0;
+
+/// Shortest particle lifetime in seconds
+const MIN_LIFETIME: f32 = 1.0;
+/// Longest particle lifetime in seconds
+const MAX_LIFETIME: f32 = 3.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Particles {
+    /// Seed of the particle positions
+    pub(crate) seed: u32,
+    /// Amount of particles per 100x100 pixels
+    pub(crate) density: f32,
+    /// Particle size in pixels
+    pub(crate) size: f32,
+    /// Average particle speed in pixels per second
+    pub(crate) speed: f32,
+    /// Point the particles are scattered from
+    pub(crate) scatter_center: (f32, f32),
+    /// How far the particles are scattered, `1.0` doubles the distance to the center
+    pub(crate) scatter: f32,
+    /// Scale factor of the surface, particles are aligned to its device pixels
+    pub(crate) scale: f32,
+}
+
+impl Default for Particles {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            density: 12.0,
+            size: 1.5,
+            speed: 12.0,
+            scatter_center: (0.0, 0.0),
+            scatter: 0.0,
+            scale: 1.0,
+        }
+    }
+}
+
+impl Particles {
+    /// Draws particles over the `bounds`
+    ///
+    /// `time` is measured in seconds
+    pub(crate) fn render(
+        &self,
+        snapshot: &gtk::Snapshot,
+        bounds: &graphene::Rect,
+        time: f32,
+        color: &gdk::RGBA,
+    ) {
+        let count = self.density.round() as u32;
+
+        if count == 0 || self.size <= 0.0 {
+            return;
+        }
+
+        let first_column = (bounds.x() / CELL_SIZE).floor() as i32;
+        let last_column = ((bounds.x() + bounds.width()) / CELL_SIZE).ceil() as i32;
+        let first_row = (bounds.y() / CELL_SIZE).floor() as i32;
+        let last_row = ((bounds.y() + bounds.height()) / CELL_SIZE).ceil() as i32;
+
+        snapshot.push_clip(bounds);
+
+        for row in first_row..last_row {
+            for column in first_column..last_column {
+                let cell_seed = hash(self.seed ^ hash(column as u32 ^ hash(row as u32)));
+
+                let origin =
+                    graphene::Point::new(column as f32 * CELL_SIZE, row as f32 * CELL_SIZE);
+
+                for index in 0..count {
+                    self.render_particle(snapshot, &origin, hash(cell_seed ^ index), time, color);
+                }
+            }
+        }
+
+        snapshot.pop();
+    }
+
+    fn render_particle(
+        &self,
+        snapshot: &gtk::Snapshot,
+        origin: &graphene::Point,
+        seed: u32,
+        time: f32,
+        color: &gdk::RGBA,
+    ) {
+        let lifetime = MIN_LIFETIME + random(seed, 0) * (MAX_LIFETIME - MIN_LIFETIME);
+        let phase = random(seed, 1) * lifetime;
+
+        let cycles = (time + phase) / lifetime;
+        let life = cycles.fract();
+
+        // Every life the particle appears in the new place
+        let seed = hash(seed ^ cycles.floor() as u32);
+
+        let angle = random(seed, 2) * std::f32::consts::TAU;
+        let speed = self.speed * (0.5 + random(seed, 3));
+        let distance = speed * life * lifetime;
+
+        let x = (random(seed, 4) * CELL_SIZE + angle.cos() * distance).rem_euclid(CELL_SIZE);
+        let y = (random(seed, 5) * CELL_SIZE + angle.sin() * distance).rem_euclid(CELL_SIZE);
+
+        let size = self
+            .snap(self.size * (0.5 + random(seed, 6)))
+            .max(1.0 / self.scale);
+
+        let (center_x, center_y) = self.scatter_center;
+        let x = origin.x() + x;
+        let y = origin.y() + y;
+        let x = x + (x - center_x) * self.scatter;
+        let y = y + (y - center_y) * self.scatter;
+
+        // Particles are only a few pixels large, so they look blurry
+        // when they are drawn between device pixels
+        let x = self.snap(x - size * 0.5);
+        let y = self.snap(y - size * 0.5);
+
+        // Fade in and fade out
+        let alpha = (life * std::f32::consts::PI).sin();
+
+        let mut color = color.clone();
+        color.set_alpha(color.alpha() * alpha);
+
+        snapshot.append_color(&color, &graphene::Rect::new(x, y, size, size));
+    }
+
+    /// Rounds the logical length to the whole device pixels
+    fn snap(&self, length: f32) -> f32 {
+        (length * self.scale).round() / self.scale
+    }
+}
+
+/// Integer hash from https://nullprogram.com/blog/2018/07/31/
+fn hash(mut x: u32) -> u32 {
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7feb352d);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846ca68b);
+    x ^= x >> 16;
+    x
+}
+
+/// Pseudo-random number between 0 and 1, `n` selects one of the numbers for the seed
+pub(super) fn random(seed: u32, n: u32) -> f32 {
+    hash(seed ^ hash(n)) as f32 / u32::MAX as f32
+}
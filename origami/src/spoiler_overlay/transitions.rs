@@ -0,0 +1,215 @@
+//! Masks of the reveal transitions
+//!
+//! Every mask is opaque where the child is revealed,
+//! so it's expected to be used with [gsk::MaskMode::InvertedAlpha]
+
+use gtk::{cairo, gdk, graphene, gsk};
+
+use super::particles::random;
+
+/// Size of the dissolving cells
+const DISSOLVE_CELL_SIZE: f32 = 12.0;
+
+/// Width of the soft wipe edge relative to the widget width
+const WIPE_EDGE: f32 = 0.2;
+
+/// Cells which disappear in random order
+pub(super) fn append_dissolve_mask(
+    snapshot: &gtk::Snapshot,
+    bounds: &graphene::Rect,
+    progress: f32,
+) {
+    let columns = (bounds.width() / DISSOLVE_CELL_SIZE).ceil() as u32;
+    let rows = (bounds.height() / DISSOLVE_CELL_SIZE).ceil() as u32;
+
+    for row in 0..rows {
+        for column in 0..columns {
+            let threshold = random(row, column);
+
+            // Cells fade out during a quarter of the animation
+            let alpha = ((progress * 1.25 - threshold) * 4.0).clamp(0.0, 1.0);
+
+            if alpha > 0.0 {
+                snapshot.append_color(
+                    &gdk::RGBA::new(0.0, 0.0, 0.0, alpha),
+                    &graphene::Rect::new(
+                        bounds.x() + column as f32 * DISSOLVE_CELL_SIZE,
+                        bounds.y() + row as f32 * DISSOLVE_CELL_SIZE,
+                        DISSOLVE_CELL_SIZE,
+                        DISSOLVE_CELL_SIZE,
+                    ),
+                );
+            }
+        }
+    }
+}
+
+/// Soft edge moving horizontally away from the side closest to the `center`
+pub(super) fn append_wipe_mask(
+    snapshot: &gtk::Snapshot,
+    bounds: &graphene::Rect,
+    center: &graphene::Point,
+    progress: f32,
+) {
+    let (start, end) = if center.x() <= bounds.x() + bounds.width() * 0.5 {
+        (bounds.top_left(), bounds.top_right())
+    } else {
+        (bounds.top_right(), bounds.top_left())
+    };
+
+    let edge = progress * (1.0 + WIPE_EDGE);
+
+    snapshot.append_linear_gradient(
+        bounds,
+        &start,
+        &end,
+        &[
+            gsk::ColorStop::new((edge - WIPE_EDGE).clamp(0.0, 1.0), gdk::RGBA::BLACK),
+            gsk::ColorStop::new(edge.clamp(0.0, 1.0), gdk::RGBA::TRANSPARENT),
+        ],
+    );
+}
+
+/// Width of the line cleared by scratching
+pub(super) const SCRATCH_WIDTH: f32 = 48.0;
+
+/// Size of the cells used to estimate the scratched area
+const SCRATCH_CELL_SIZE: f32 = 16.0;
+
+/// Lines drawn by the user in the scratch mode
+pub(super) fn append_scratch_mask(
+    snapshot: &gtk::Snapshot,
+    bounds: &graphene::Rect,
+    strokes: &[Vec<graphene::Point>],
+) {
+    let context = snapshot.append_cairo(bounds);
+
+    context.set_line_width(SCRATCH_WIDTH as f64);
+    context.set_line_cap(cairo::LineCap::Round);
+    context.set_line_join(cairo::LineJoin::Round);
+
+    for stroke in strokes {
+        let Some(first) = stroke.first() else {
+            continue;
+        };
+
+        context.move_to(first.x() as f64, first.y() as f64);
+
+        // A single point is drawn as a dot
+        for point in stroke.iter().skip(1).chain(stroke.last()) {
+            context.line_to(point.x() as f64, point.y() as f64);
+        }
+    }
+
+    if let Err(e) = context.stroke() {
+        log::warn!("can't draw the scratch mask {e}");
+    }
+}
+
+/// Approximate part of the `bounds` covered by the strokes
+pub(super) fn scratch_coverage(bounds: &graphene::Rect, strokes: &[Vec<graphene::Point>]) -> f32 {
+    let columns = (bounds.width() / SCRATCH_CELL_SIZE).ceil().max(1.0) as usize;
+    let rows = (bounds.height() / SCRATCH_CELL_SIZE).ceil().max(1.0) as usize;
+
+    let mut covered = vec![false; columns * rows];
+
+    let radius = SCRATCH_WIDTH * 0.5;
+
+    for stroke in strokes {
+        for (start, end) in stroke
+            .iter()
+            .zip(stroke.iter().skip(1).chain(stroke.last()))
+        {
+            // Sample the segment often enough to not skip any cell
+            let steps = (start.distance(end).0 / (SCRATCH_CELL_SIZE * 0.5))
+                .ceil()
+                .max(1.0);
+
+            for step in 0..=steps as usize {
+                let point = start.interpolate(end, step as f64 / steps as f64);
+
+                let x = point.x() - bounds.x();
+                let y = point.y() - bounds.y();
+
+                let first_column = ((x - radius) / SCRATCH_CELL_SIZE).floor().max(0.0) as usize;
+                let last_column =
+                    (((x + radius) / SCRATCH_CELL_SIZE).ceil().max(0.0) as usize).min(columns);
+                let first_row = ((y - radius) / SCRATCH_CELL_SIZE).floor().max(0.0) as usize;
+                let last_row =
+                    (((y + radius) / SCRATCH_CELL_SIZE).ceil().max(0.0) as usize).min(rows);
+
+                for row in first_row..last_row {
+                    for column in first_column..last_column {
+                        let cell_x = (column as f32 + 0.5) * SCRATCH_CELL_SIZE;
+                        let cell_y = (row as f32 + 0.5) * SCRATCH_CELL_SIZE;
+
+                        if (cell_x - x).hypot(cell_y - y) <= radius {
+                            covered[row * columns + column] = true;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    covered.iter().filter(|cell| **cell).count() as f32 / covered.len() as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(x: f32, y: f32) -> graphene::Point {
+        graphene::Point::new(x, y)
+    }
+
+    /// 10 by 6 cells
+    fn bounds() -> graphene::Rect {
+        graphene::Rect::new(0.0, 0.0, 160.0, 96.0)
+    }
+
+    #[test]
+    fn no_strokes_cover_nothing() {
+        assert_eq!(scratch_coverage(&bounds(), &[]), 0.0);
+        assert_eq!(scratch_coverage(&bounds(), &[vec![]]), 0.0);
+    }
+
+    #[test]
+    fn single_point_covers_a_dot() {
+        let coverage = scratch_coverage(&bounds(), &[vec![point(48.0, 48.0)]]);
+
+        // Only the four cells around the point are closer than the radius
+        assert_eq!(coverage, 4.0 / 60.0);
+    }
+
+    #[test]
+    fn strokes_over_every_row_cover_everything() {
+        let strokes: Vec<_> = (0..3)
+            .map(|row| {
+                let y = 16.0 + row as f32 * 32.0;
+                vec![point(0.0, y), point(160.0, y)]
+            })
+            .collect();
+
+        assert_eq!(scratch_coverage(&bounds(), &strokes), 1.0);
+    }
+
+    #[test]
+    fn points_outside_cover_only_nearby_cells() {
+        let far = scratch_coverage(&bounds(), &[vec![point(-100.0, -100.0)]]);
+        assert_eq!(far, 0.0);
+
+        // 2 by 2 cells, the dot reaches the left column
+        let small = graphene::Rect::new(0.0, 0.0, 32.0, 32.0);
+        let near = scratch_coverage(&small, &[vec![point(-10.0, 16.0)]]);
+        assert_eq!(near, 0.5);
+    }
+
+    #[test]
+    fn coverage_is_relative_to_the_bounds() {
+        let moved = graphene::Rect::new(100.0, 100.0, 160.0, 96.0);
+        let coverage = scratch_coverage(&moved, &[vec![point(148.0, 148.0)]]);
+
+        assert_eq!(coverage, 4.0 / 60.0);
+    }
+}
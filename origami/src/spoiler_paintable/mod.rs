@@ -0,0 +1,574 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use glib::clone;
+use gtk::{gdk, glib, graphene, gsk};
+use std::cell::{Cell, RefCell};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::animation_driver::Ticker;
+use crate::blur_cache::{BlurCache, BlurKey};
+use crate::spoiler_overlay::{
+    append_blur, append_reveal_gradient, blur_downscale, default_tint_color, particles_time,
+    reveal_radius, Particles, BLUR_REFRESH_INTERVAL, REVEAL_DURATION,
+};
+
+/// Running reveal or hide animation
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Animation {
+    /// Time of the ticker when the animation started
+    start_time: i64,
+    from: f64,
+    to: f64,
+}
+
+mod imp {
+    use super::*;
+
+    #[derive(glib::Properties)]
+    #[properties(wrapper_type = super::SpoilerPaintable)]
+    pub struct SpoilerPaintable {
+        #[property(get, set = Self::set_paintable)]
+        pub(super) paintable: RefCell<Option<gdk::Paintable>>,
+        pub(super) paintable_handlers: RefCell<Vec<glib::SignalHandlerId>>,
+
+        #[property(get, set = Self::set_hidden)]
+        pub(super) hidden: Cell<bool>,
+
+        #[property(get, set = Self::set_reveal_progress, minimum = 0.0, maximum = 1.0, default_value = 1.0)]
+        pub(super) reveal_progress: Cell<f64>,
+        pub(super) animation: Cell<Option<Animation>>,
+
+        #[property(get, set = Self::set_blur_radius, minimum = 0.0, default_value = 128.0)]
+        pub(super) blur_radius: Cell<f64>,
+
+        #[property(get, set = Self::set_tint_color)]
+        pub(super) tint_color: RefCell<Option<gdk::RGBA>>,
+
+        #[property(get, set = Self::set_particle_color)]
+        pub(super) particle_color: RefCell<Option<gdk::RGBA>>,
+
+        /// Widget whose frame clock animates the paintable
+        pub(super) widget: glib::WeakRef<gtk::Widget>,
+        pub(super) widget_handlers: RefCell<Vec<glib::SignalHandlerId>>,
+        pub(super) ticker: Ticker,
+
+        pub(super) start_time: Cell<i64>,
+        /// Whether the paintable was drawn at least once
+        pub(super) displayed: Cell<bool>,
+        /// Blurred paintable with the key it was rendered for
+        pub(super) blurred_texture_cache: RefCell<Option<(gdk::Texture, BlurKey)>>,
+        pub(super) last_blur_refresh: Cell<i64>,
+        pub(super) blur_refresh_source: RefCell<Option<glib::SourceId>>,
+    }
+
+    impl Default for SpoilerPaintable {
+        fn default() -> Self {
+            Self {
+                paintable: Default::default(),
+                paintable_handlers: Default::default(),
+                hidden: Default::default(),
+                reveal_progress: Cell::new(1.0),
+                animation: Default::default(),
+                blur_radius: Cell::new(128.0),
+                tint_color: Default::default(),
+                particle_color: Default::default(),
+                widget: Default::default(),
+                widget_handlers: Default::default(),
+                ticker: Default::default(),
+                start_time: Default::default(),
+                displayed: Default::default(),
+                blurred_texture_cache: Default::default(),
+                last_blur_refresh: Default::default(),
+                blur_refresh_source: Default::default(),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for SpoilerPaintable {
+        const NAME: &'static str = "OriSpoilerPaintable";
+        type Type = super::SpoilerPaintable;
+        type Interfaces = (gdk::Paintable,);
+    }
+
+    impl ObjectImpl for SpoilerPaintable {
+        fn properties() -> &'static [glib::ParamSpec] {
+            Self::derived_properties()
+        }
+
+        fn set_property(&self, id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            self.derived_set_property(id, value, pspec)
+        }
+
+        fn property(&self, id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            self.derived_property(id, pspec)
+        }
+
+        fn dispose(&self) {
+            self.disconnect_paintable();
+            self.set_widget(None);
+
+            if let Some(source) = self.blur_refresh_source.take() {
+                source.remove();
+            }
+        }
+    }
+
+    impl PaintableImpl for SpoilerPaintable {
+        fn intrinsic_width(&self) -> i32 {
+            self.paintable
+                .borrow()
+                .as_ref()
+                .map_or(0, |paintable| paintable.intrinsic_width())
+        }
+
+        fn intrinsic_height(&self) -> i32 {
+            self.paintable
+                .borrow()
+                .as_ref()
+                .map_or(0, |paintable| paintable.intrinsic_height())
+        }
+
+        fn intrinsic_aspect_ratio(&self) -> f64 {
+            self.paintable
+                .borrow()
+                .as_ref()
+                .map_or(0.0, |paintable| paintable.intrinsic_aspect_ratio())
+        }
+
+        fn snapshot(&self, snapshot: &gdk::Snapshot, width: f64, height: f64) {
+            let Some(paintable) = self.paintable.borrow().clone() else {
+                return;
+            };
+
+            self.displayed.set(true);
+
+            let snapshot = snapshot.downcast_ref::<gtk::Snapshot>().unwrap();
+
+            paintable.snapshot(snapshot, width, height);
+
+            let progress = self.reveal_progress.get() as f32;
+
+            if progress >= 1.0 {
+                return;
+            }
+
+            let bounds = graphene::Rect::new(0.0, 0.0, width as f32, height as f32);
+            let center = bounds.center();
+            let radius = reveal_radius(&bounds, &center, progress);
+
+            if radius > 0.0 {
+                snapshot.push_mask(gsk::MaskMode::InvertedAlpha);
+                append_reveal_gradient(snapshot, &bounds, &center, radius, progress);
+                snapshot.pop();
+            }
+
+            match self.blurred_texture(&paintable, &bounds) {
+                Some(texture) => snapshot.append_texture(&texture, &bounds),
+                // Without the renderer the paintable is blurred every frame
+                None => {
+                    let blur_radius = self.blur_radius.get() as f32;
+                    append_blur(snapshot, &bounds, blur_radius, &self.tint(), |snapshot| {
+                        paintable.snapshot(snapshot, width, height)
+                    });
+                }
+            }
+
+            let time = particles_time(self.ticker.time() - self.start_time.get());
+            let widget = self.widget.upgrade();
+
+            let color = self.particle_color.borrow().clone().unwrap_or_else(|| {
+                match &widget {
+                    Some(widget) => widget.color(),
+                    // Contrasts with the default tint
+                    None if adw::StyleManager::default().is_dark() => gdk::RGBA::WHITE,
+                    None => gdk::RGBA::BLACK,
+                }
+            });
+
+            let particles = Particles {
+                scale: widget.map_or(1, |widget| widget.scale_factor()) as f32,
+                ..Default::default()
+            };
+
+            particles.render(snapshot, &bounds, time, &color);
+
+            if radius > 0.0 {
+                snapshot.pop();
+            }
+        }
+    }
+
+    impl SpoilerPaintable {
+        fn set_paintable(&self, paintable: Option<gdk::Paintable>) {
+            let obj = self.obj();
+
+            self.disconnect_paintable();
+
+            if let Some(paintable) = &paintable {
+                self.paintable_handlers.replace(vec![
+                    paintable.connect_invalidate_contents(clone!(@weak obj => move |_| {
+                        obj.imp().schedule_blur_refresh();
+                        obj.invalidate_contents();
+                    })),
+                    paintable.connect_invalidate_size(clone!(@weak obj => move |_| {
+                        obj.invalidate_size();
+                    })),
+                ]);
+            }
+
+            self.paintable.replace(paintable);
+            self.blurred_texture_cache.take();
+
+            obj.invalidate_size();
+            obj.invalidate_contents();
+        }
+
+        /// Refreshes the blur after the paintable redraws
+        ///
+        /// Animated paintables redraw every frame,
+        /// so the blur is refreshed at most once per [BLUR_REFRESH_INTERVAL]
+        fn schedule_blur_refresh(&self) {
+            // The blur isn't visible, it's rendered again when the paintable is hidden
+            if self.reveal_progress.get() >= 1.0 {
+                self.refresh_blur();
+                return;
+            }
+
+            if self.blur_refresh_source.borrow().is_some() {
+                return;
+            }
+
+            let elapsed = glib::monotonic_time() - self.last_blur_refresh.get();
+
+            if elapsed >= BLUR_REFRESH_INTERVAL {
+                self.refresh_blur();
+                return;
+            }
+
+            let obj = self.obj();
+
+            let source = glib::timeout_add_local_once(
+                std::time::Duration::from_micros((BLUR_REFRESH_INTERVAL - elapsed) as u64),
+                clone!(@weak obj => move || {
+                    obj.imp().blur_refresh_source.take();
+                    obj.imp().refresh_blur();
+                    obj.invalidate_contents();
+                }),
+            );
+
+            self.blur_refresh_source.replace(Some(source));
+        }
+
+        /// Drops the blurred texture of the old contents
+        fn refresh_blur(&self) {
+            self.last_blur_refresh.set(glib::monotonic_time());
+            self.blurred_texture_cache.take();
+
+            if let Some(paintable) = &*self.paintable.borrow() {
+                BlurCache::forget(paintable);
+            }
+        }
+
+        fn disconnect_paintable(&self) {
+            let handlers = self.paintable_handlers.take();
+
+            if let Some(paintable) = &*self.paintable.borrow() {
+                for handler in handlers {
+                    paintable.disconnect(handler);
+                }
+            }
+        }
+
+        pub(super) fn set_widget(&self, widget: Option<&gtk::Widget>) {
+            let obj = self.obj();
+
+            let handlers = self.widget_handlers.take();
+
+            if let Some(old) = self.widget.upgrade() {
+                for handler in handlers {
+                    old.disconnect(handler);
+                }
+            }
+
+            // The old widget can be already destroyed, e.g. a recycled grid item
+            self.ticker.reset();
+
+            self.widget.set(widget);
+
+            let Some(widget) = widget else {
+                return;
+            };
+
+            // Ticking pauses while the widget isn't visible on the screen
+            self.widget_handlers.replace(vec![
+                widget.connect_map(clone!(@weak obj => move |widget| {
+                    obj.imp().ticker.update(widget);
+                })),
+                widget.connect_unmap(clone!(@weak obj => move |widget| {
+                    obj.imp().ticker.update(widget);
+                })),
+            ]);
+
+            self.start_ticking();
+        }
+
+        fn set_hidden(&self, hidden: bool) {
+            if self.hidden.replace(hidden) == hidden {
+                return;
+            }
+
+            let from = self.reveal_progress.get();
+            let to = if hidden { 0.0 } else { 1.0 };
+
+            if hidden && from >= 1.0 {
+                self.start_time.set(self.ticker.time());
+            }
+
+            // Nobody saw the paintable yet or there is no clock to animate it
+            if !self.displayed.get() || self.widget.upgrade().is_none() {
+                self.animation.take();
+                self.reveal_progress.set(to);
+                self.obj().notify("reveal-progress");
+                self.obj().invalidate_contents();
+                self.start_ticking();
+                return;
+            }
+
+            self.animation.set(Some(Animation {
+                start_time: self.ticker.time(),
+                from,
+                to,
+            }));
+
+            self.start_ticking();
+        }
+
+        fn set_reveal_progress(&self, progress: f64) {
+            // The app controls the progress now
+            self.animation.take();
+
+            if progress < 1.0 && self.reveal_progress.get() >= 1.0 {
+                self.start_time.set(self.ticker.time());
+            }
+
+            self.reveal_progress.set(progress);
+            self.start_ticking();
+            self.obj().invalidate_contents();
+        }
+
+        fn set_blur_radius(&self, radius: f64) {
+            self.blur_radius.set(radius);
+            self.obj().invalidate_contents();
+        }
+
+        fn set_tint_color(&self, color: Option<gdk::RGBA>) {
+            self.tint_color.replace(color);
+            self.obj().invalidate_contents();
+        }
+
+        fn set_particle_color(&self, color: Option<gdk::RGBA>) {
+            self.particle_color.replace(color);
+            self.obj().invalidate_contents();
+        }
+
+        fn tint(&self) -> gdk::RGBA {
+            self.tint_color
+                .borrow()
+                .clone()
+                .unwrap_or_else(default_tint_color)
+        }
+
+        /// Blurred paintable shared through the [BlurCache]
+        ///
+        /// Returns [None] when the widget isn't realized
+        fn blurred_texture(
+            &self,
+            paintable: &gdk::Paintable,
+            bounds: &graphene::Rect,
+        ) -> Option<gdk::Texture> {
+            let widget = self.widget.upgrade()?;
+            let renderer = widget.native()?.renderer();
+            let scale_factor = widget.scale_factor();
+
+            let radius = self.blur_radius.get() as f32;
+            let tint = self.tint();
+
+            // Key changes with the size, the scale and the settings
+            let mut hasher = DefaultHasher::new();
+            Self::NAME.hash(&mut hasher);
+            radius.to_bits().hash(&mut hasher);
+            tint.to_string().hash(&mut hasher);
+
+            let key = BlurKey {
+                content: paintable.as_ptr() as usize,
+                size: (
+                    bounds.width().to_bits(),
+                    bounds.height().to_bits(),
+                    scale_factor,
+                ),
+                settings: hasher.finish(),
+            };
+
+            if let Some((texture, cached_key)) = &*self.blurred_texture_cache.borrow() {
+                if *cached_key == key {
+                    return Some(texture.clone());
+                }
+            }
+
+            let texture = match BlurCache::lookup(&key) {
+                Some(texture) => texture,
+                None => {
+                    // Details are lost in the blur anyway
+                    let scale = blur_downscale(radius, scale_factor as f32);
+
+                    let snapshot = gtk::Snapshot::new();
+                    snapshot.scale(scale, scale);
+                    append_blur(&snapshot, bounds, radius, &tint, |snapshot| {
+                        paintable.snapshot(snapshot, bounds.width() as f64, bounds.height() as f64)
+                    });
+
+                    let viewport = graphene::Rect::new(
+                        0.0,
+                        0.0,
+                        (bounds.width() * scale).ceil(),
+                        (bounds.height() * scale).ceil(),
+                    );
+                    let texture = renderer.render_texture(snapshot.to_node()?, Some(&viewport));
+
+                    BlurCache::insert(key.clone(), paintable, &texture);
+                    texture
+                }
+            };
+
+            self.blurred_texture_cache
+                .replace(Some((texture.clone(), key)));
+
+            Some(texture)
+        }
+
+        fn is_animating(&self) -> bool {
+            self.animation.get().is_some() || self.reveal_progress.get() < 1.0
+        }
+
+        fn start_ticking(&self) {
+            if self.ticker.is_running() || !self.is_animating() {
+                return;
+            }
+
+            let Some(widget) = self.widget.upgrade() else {
+                return;
+            };
+
+            let obj = self.obj();
+
+            self.ticker.start(
+                &widget,
+                clone!(@weak obj => @default-return false, move |_| obj.imp().tick()),
+            );
+        }
+
+        /// Moves the animation and redraws the paintable
+        ///
+        /// Returns `false` when nothing is animated anymore
+        fn tick(&self) -> bool {
+            let obj = self.obj();
+
+            if let Some(animation) = self.animation.get() {
+                let duration =
+                    REVEAL_DURATION as f64 * 1000.0 * (animation.to - animation.from).abs();
+                let elapsed = (self.ticker.time() - animation.start_time) as f64;
+                let t = if duration > 0.0 {
+                    (elapsed / duration).min(1.0)
+                } else {
+                    1.0
+                };
+
+                let eased = adw::Easing::EaseInOutCubic.ease(t);
+                let progress = animation.from + (animation.to - animation.from) * eased;
+
+                self.reveal_progress.set(progress);
+                obj.notify("reveal-progress");
+
+                if t >= 1.0 {
+                    self.animation.take();
+                }
+            }
+
+            obj.invalidate_contents();
+
+            self.is_animating()
+        }
+    }
+}
+
+glib::wrapper! {
+    #[doc(alias = "OriSpoilerPaintable")]
+    /// Paintable which hides another paintable under the spoiler
+    ///
+    /// Draws the same blur and particles as [SpoilerOverlay](crate::SpoilerOverlay),
+    /// so spoilers can be displayed by [gtk::Picture] in grids and thumbnails.
+    /// The blurred paintable is shared through the [BlurCache](crate::BlurCache).
+    ///
+    /// # Properties
+    ///
+    /// * Paintable: [Option]<[gdk::Paintable]>.
+    /// Paintable displayed under the spoiler.
+    ///
+    /// * Hidden: [bool].
+    /// Set to [true] to hide the paintable, changes are animated
+    /// once the paintable was displayed.
+    ///
+    /// * Reveal progress: [f64] between 0 and 1.
+    /// Progress of the reveal animation, setting it stops the running animation.
+    ///
+    /// * Blur radius: [f64].
+    /// Radius of the blur in pixels, `128` by default.
+    ///
+    /// * Tint color: [Option]<[gdk::RGBA]>.
+    /// Color under the blurred paintable, follows the color scheme when not set.
+    ///
+    /// * Particle color: [Option]<[gdk::RGBA]>.
+    /// Color of the particles, follows the CSS color of the widget when not set.
+    ///
+    /// # Animation
+    ///
+    /// A paintable doesn't know where it's drawn,
+    /// so it's animated by the frame clock of the widget set with [SpoilerPaintable::set_widget]
+    /// and follows the [AnimationDriver](crate::AnimationDriver).
+    /// Without the widget the changes aren't animated and the particles stand still.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # let texture: gtk::gdk::Texture = unimplemented!();
+    /// let spoiler = origami::SpoilerPaintable::new(Some(&texture));
+    /// spoiler.set_hidden(true);
+    ///
+    /// let picture = gtk::Picture::for_paintable(&spoiler);
+    /// spoiler.set_widget(Some(&picture));
+    /// ```
+    pub struct SpoilerPaintable(ObjectSubclass<imp::SpoilerPaintable>)
+        @implements gdk::Paintable;
+}
+
+impl SpoilerPaintable {
+    pub fn new(paintable: Option<&impl IsA<gdk::Paintable>>) -> Self {
+        glib::Object::builder()
+            .property("paintable", paintable.map(|p| p.as_ref().clone()))
+            .build()
+    }
+
+    /// Widget displaying the paintable
+    pub fn widget(&self) -> Option<gtk::Widget> {
+        self.imp().widget.upgrade()
+    }
+
+    /// Sets the widget whose frame clock animates the paintable
+    ///
+    /// The paintable keeps a weak reference,
+    /// so it can be displayed by the same widget without a reference cycle
+    pub fn set_widget(&self, widget: Option<&impl IsA<gtk::Widget>>) {
+        self.imp().set_widget(widget.map(|w| w.upcast_ref()));
+    }
+}
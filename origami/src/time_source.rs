@@ -0,0 +1,84 @@
+//! Sources of the animation time
+
+use gtk::gdk;
+use gtk::subclass::prelude::*;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::AnimationDriver;
+
+/// Duration of a frame used by [ManualClock::step]
+const FRAME_DURATION: Duration = Duration::from_micros(16_667);
+
+/// Time read by the animations of [AnimationDriver]
+///
+/// It drives the particles, shimmers, loading indicators
+/// and the reveal animation of [SpoilerPaintable](crate::SpoilerPaintable).
+/// Reveals of [SpoilerOverlay](crate::SpoilerOverlay) and [SpoilerLabel](crate::SpoilerLabel)
+/// run on [adw::TimedAnimation] and follow the real time,
+/// as well as the `reveal-timeout` countdown and the blur refresh throttling.
+pub trait TimeSource: 'static {
+    /// Time of the current frame of the `clock` in microseconds
+    fn frame_time(&self, clock: &gdk::FrameClock) -> i64;
+}
+
+/// Time of the frame clock, used by default
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FrameClockTime;
+
+impl TimeSource for FrameClockTime {
+    fn frame_time(&self, clock: &gdk::FrameClock) -> i64 {
+        clock.frame_time()
+    }
+}
+
+/// Virtual time which only moves when it's advanced
+///
+/// Every advance ticks all running animations,
+/// so the widgets can be rendered at known frames.
+/// Clones of the clock share the same time.
+///
+/// Only the animations listed in [TimeSource] are stepped,
+/// set `reveal-progress` to render a reveal at a known point.
+///
+/// # Example
+/// ```no_run
+/// let clock = origami::ManualClock::new();
+/// origami::AnimationDriver::default().set_time_source(clock.clone());
+///
+/// // Particles move by 100ms
+/// clock.advance(std::time::Duration::from_millis(100));
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct ManualClock {
+    time: Rc<Cell<i64>>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current time in microseconds
+    pub fn time(&self) -> i64 {
+        self.time.get()
+    }
+
+    /// Moves the time forward and ticks the animations
+    pub fn advance(&self, duration: Duration) {
+        self.time.set(self.time.get() + duration.as_micros() as i64);
+        AnimationDriver::default().imp().dispatch();
+    }
+
+    /// Advances the time by a single 60 FPS frame
+    pub fn step(&self) {
+        self.advance(FRAME_DURATION);
+    }
+}
+
+impl TimeSource for ManualClock {
+    fn frame_time(&self, _clock: &gdk::FrameClock) -> i64 {
+        self.time.get()
+    }
+}
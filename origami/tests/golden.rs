@@ -0,0 +1,236 @@
+//! Golden image tests
+//!
+//! Every case builds a widget in a window, renders it with [gsk::CairoRenderer]
+//! and compares the result with `tests/golden/<name>.png`.
+//!
+//! The tests need a display, but not a GPU, so they are ignored by default
+//! and run on a headless CI box with `xvfb-run cargo test -- --ignored`
+//! or under a headless compositor.
+//!
+//! Run with `ORIGAMI_BLESS=1` to write the new golden images,
+//! failed renders are saved to the `CARGO_TARGET_TMPDIR`.
+
+use adw::prelude::*;
+use gtk::{gdk, glib, graphene, gsk};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Channels differing less than that are considered equal
+const CHANNEL_TOLERANCE: u8 = 16;
+/// Part of pixels allowed to differ
+const PIXEL_TOLERANCE: f64 = 0.01;
+
+/// Longest time to wait for the window to appear
+const MAP_TIMEOUT: Duration = Duration::from_secs(5);
+
+const CSS: &str = "
+.golden-block {
+    background: #3584e4;
+    min-width: 160px;
+    min-height: 96px;
+}
+";
+
+#[test]
+#[ignore = "needs a display, run with `xvfb-run cargo test -- --ignored`"]
+fn golden_images() {
+    // Software renderer gives the same results without a GPU
+    std::env::set_var("GSK_RENDERER", "cairo");
+    std::env::set_var("ADW_DISABLE_PORTAL", "1");
+
+    gtk::init().expect("golden image tests need a display");
+
+    adw::init().unwrap();
+    origami::init();
+
+    adw::StyleManager::default().set_color_scheme(adw::ColorScheme::ForceLight);
+
+    let provider = gtk::CssProvider::new();
+    provider.load_from_data(CSS);
+    gtk::style_context_add_provider_for_display(
+        &gdk::Display::default().unwrap(),
+        &provider,
+        gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
+    );
+
+    // Particles and spinners are rendered at known frames
+    let clock = origami::ManualClock::new();
+    origami::AnimationDriver::default().set_time_source(clock.clone());
+
+    let mut failures = Vec::new();
+
+    let cases: &[(&str, fn() -> gtk::Widget)] = &[
+        ("loading_indicator", loading_indicator),
+        ("shimmer_effect", shimmer_effect),
+        ("spoiler_overlay_hidden", spoiler_overlay_hidden),
+        ("spoiler_overlay_reveal", spoiler_overlay_reveal),
+    ];
+
+    for (name, build) in cases {
+        let widget = build();
+        let texture = render(&widget, &clock);
+
+        if let Err(error) = compare(name, &texture) {
+            failures.push(format!("{name}: {error}"));
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+fn loading_indicator() -> gtk::Widget {
+    let indicator = glib::Object::new::<origami::LoadingIndicator>();
+    indicator.set_progress(0.3);
+    indicator.set_size_request(48, 48);
+    indicator.upcast()
+}
+
+fn shimmer_effect() -> gtk::Widget {
+    let shimmer = glib::Object::new::<origami::ShimmerEffect>();
+    shimmer.set_child(Some(&block()));
+    shimmer.set_playing(true);
+    shimmer.upcast()
+}
+
+fn spoiler_overlay_hidden() -> gtk::Widget {
+    let overlay = spoiler_overlay();
+    overlay.set_hidden(true);
+    overlay.set_reveal_progress(0.0);
+    overlay.upcast()
+}
+
+fn spoiler_overlay_reveal() -> gtk::Widget {
+    let overlay = spoiler_overlay();
+    overlay.set_hidden(true);
+    overlay.set_reveal_progress(0.4);
+    overlay.upcast()
+}
+
+fn spoiler_overlay() -> origami::SpoilerOverlay {
+    let overlay = glib::Object::new::<origami::SpoilerOverlay>();
+    // Shaders aren't supported by the cairo renderer
+    overlay.set_blur_backend(origami::BlurBackend::Cpu);
+    overlay.set_child(Some(&block()));
+    overlay
+}
+
+/// Solid rectangle, text would look different with other fonts
+fn block() -> gtk::Widget {
+    let block = gtk::Box::new(gtk::Orientation::Vertical, 0);
+    block.add_css_class("golden-block");
+    block.upcast()
+}
+
+/// Shows the widget in a window and renders it at half a second of the animation
+fn render(widget: &gtk::Widget, clock: &origami::ManualClock) -> gdk::Texture {
+    let window = gtk::Window::builder()
+        .child(widget)
+        .decorated(false)
+        .resizable(false)
+        .build();
+    window.present();
+
+    let context = glib::MainContext::default();
+    let start = Instant::now();
+
+    while !widget.is_mapped() || widget.width() == 0 {
+        assert!(start.elapsed() < MAP_TIMEOUT, "window didn't appear");
+        context.iteration(true);
+    }
+
+    clock.advance(Duration::from_millis(500));
+
+    while context.pending() {
+        context.iteration(false);
+    }
+
+    let width = widget.width();
+    let height = widget.height();
+
+    let paintable = gtk::WidgetPaintable::new(Some(widget));
+    let snapshot = gtk::Snapshot::new();
+    paintable.snapshot(&snapshot, width as f64, height as f64);
+    let node = snapshot.to_node().expect("widget rendered nothing");
+
+    let renderer = gsk::CairoRenderer::new();
+    renderer.realize(None::<&gdk::Surface>).unwrap();
+    let viewport = graphene::Rect::new(0.0, 0.0, width as f32, height as f32);
+    let texture = renderer.render_texture(node, Some(&viewport));
+    renderer.unrealize();
+
+    window.destroy();
+
+    texture
+}
+
+fn compare(name: &str, texture: &gdk::Texture) -> Result<(), String> {
+    let golden_path = golden_dir().join(format!("{name}.png"));
+
+    if std::env::var_os("ORIGAMI_BLESS").is_some() {
+        std::fs::create_dir_all(golden_dir()).map_err(|e| e.to_string())?;
+        texture
+            .save_to_png(&golden_path)
+            .map_err(|e| e.to_string())?;
+        return Ok(());
+    }
+
+    let result = gdk::Texture::from_filename(&golden_path)
+        .map_err(|_| {
+            format!(
+                "missing {}, run with ORIGAMI_BLESS=1",
+                golden_path.display()
+            )
+        })
+        .and_then(|golden| diff(&golden, texture));
+
+    if result.is_err() {
+        let actual_path = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("{name}.png"));
+        if texture.save_to_png(&actual_path).is_ok() {
+            eprintln!("{name}: rendered image saved to {}", actual_path.display());
+        }
+    }
+
+    result
+}
+
+fn diff(golden: &gdk::Texture, texture: &gdk::Texture) -> Result<(), String> {
+    let size = (texture.width(), texture.height());
+    let golden_size = (golden.width(), golden.height());
+
+    if size != golden_size {
+        return Err(format!("size {size:?} differs from {golden_size:?}"));
+    }
+
+    let pixels = download(texture);
+    let golden_pixels = download(golden);
+
+    let different = pixels
+        .chunks_exact(4)
+        .zip(golden_pixels.chunks_exact(4))
+        .filter(|(pixel, golden)| {
+            pixel
+                .iter()
+                .zip(golden.iter())
+                .any(|(a, b)| a.abs_diff(*b) > CHANNEL_TOLERANCE)
+        })
+        .count();
+
+    let total = (size.0 * size.1) as usize;
+
+    if different as f64 > total as f64 * PIXEL_TOLERANCE {
+        Err(format!("{different} of {total} pixels differ"))
+    } else {
+        Ok(())
+    }
+}
+
+fn download(texture: &gdk::Texture) -> Vec<u8> {
+    let stride = texture.width() as usize * 4;
+    let mut data = vec![0; stride * texture.height() as usize];
+    texture.download(&mut data, stride);
+    data
+}
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}